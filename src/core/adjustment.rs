@@ -0,0 +1,257 @@
+use std::time::{Duration, Instant};
+use skia_safe::{color_filters, ColorFilter, Paint};
+use crate::core::{Color, Overlay, color::lerp};
+
+/// Rec. 709 weights used for "grey" by saturation and tint
+const LUMA: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+type ColorMatrix = [[f32; 5]; 4];
+
+const IDENTITY: ColorMatrix = [
+    [1.0, 0.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0, 0.0],
+];
+
+/// Adjustments applied to the whole overlay once per frame, when it's composited
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorAdjustment {
+    /// Master opacity, 0 hides the overlay and 1 leaves it as drawn
+    pub opacity: f32,
+    /// 0 is greyscale, 1 unchanged and above 1 more vivid
+    pub saturation: f32,
+    /// Added to every channel, -1 to 1 with 0 unchanged
+    pub brightness: f32,
+    /// Spreads channels away from mid grey, 0 is flat grey and 1 unchanged
+    pub contrast: f32,
+    /// Colorizes the overlay towards this color, its alpha is the strength
+    pub tint: Color,
+}
+
+impl Default for ColorAdjustment {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl ColorAdjustment {
+    pub const IDENTITY: ColorAdjustment = ColorAdjustment {
+        opacity: 1.0,
+        saturation: 1.0,
+        brightness: 0.0,
+        contrast: 1.0,
+        tint: Color::TRANSPARENT,
+    };
+
+    /// Only changes the master opacity
+    pub fn opacity(opacity: f32) -> Self {
+        Self { opacity, ..Self::IDENTITY }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.opacity >= 1.0 && self.matrix().is_none()
+    }
+
+    /// Row-major 4x5 matrix for non-premultiplied sRGB values, `None` when it doesn't change colors.
+    /// Opacity isn't part of it, it's applied as the alpha of the composited layer.
+    pub fn matrix(&self) -> Option<[f32; 20]> {
+        if self.saturation == 1.0 && self.brightness == 0.0 && self.contrast == 1.0 && self.tint.a == 0 {
+            return None;
+        }
+
+        // Contrast and brightness first, then saturation, then the tint
+        let offset = 0.5 * (1.0 - self.contrast) + self.brightness;
+        let mut levels = IDENTITY;
+        for (channel, row) in levels.iter_mut().take(3).enumerate() {
+            row[channel] = self.contrast;
+            row[4] = offset;
+        }
+
+        let mut saturation = IDENTITY;
+        for (channel, row) in saturation.iter_mut().take(3).enumerate() {
+            for (column, value) in row.iter_mut().take(3).enumerate() {
+                let identity = if channel == column { 1.0 } else { 0.0 };
+                *value = lerp(LUMA[column], identity, self.saturation);
+            }
+        }
+
+        let [tint_r, tint_g, tint_b, strength] = self.tint.to_f32();
+        let mut tint = IDENTITY;
+        for (channel, row) in tint.iter_mut().take(3).enumerate() {
+            let tint_channel = [tint_r, tint_g, tint_b][channel];
+            for (column, value) in row.iter_mut().take(3).enumerate() {
+                let identity = if channel == column { 1.0 } else { 0.0 };
+                *value = lerp(identity, tint_channel * LUMA[column], strength);
+            }
+        }
+
+        let matrix = concat(&tint, &concat(&saturation, &levels));
+        let mut row_major = [0.0; 20];
+        for (target, value) in row_major.iter_mut().zip(matrix.iter().flatten()) {
+            *target = *value;
+        }
+        Some(row_major)
+    }
+
+    /// Applies the adjustment to a single color, matching what compositing does to pixels
+    pub fn apply(&self, color: Color) -> Color {
+        let color = color.multiply_alpha(self.opacity);
+        let Some(matrix) = self.matrix() else {
+            return color;
+        };
+
+        let input = color.to_f32();
+        let output: [f32; 4] = std::array::from_fn(|row| {
+            let weights = &matrix[row * 5..row * 5 + 5];
+            let value: f32 = weights[..4].iter().zip(input).map(|(weight, channel)| weight * channel).sum();
+            (value + weights[4]).clamp(0.0, 1.0)
+        });
+
+        Color::from_f32(output)
+    }
+
+    /// Blends towards `other`, `t` from 0 (self) to 1 (other)
+    pub fn lerp(&self, other: &ColorAdjustment, t: f32) -> Self {
+        // A tint fading in or out keeps its hue instead of passing through black
+        let from_tint = if self.tint.a == 0 { other.tint.with_alpha(0) } else { self.tint };
+        let to_tint = if other.tint.a == 0 { self.tint.with_alpha(0) } else { other.tint };
+
+        Self {
+            opacity: lerp(self.opacity, other.opacity, t),
+            saturation: lerp(self.saturation, other.saturation, t),
+            brightness: lerp(self.brightness, other.brightness, t),
+            contrast: lerp(self.contrast, other.contrast, t),
+            tint: from_tint.lerp(to_tint, t),
+        }
+    }
+
+    /// Skia color filter for everything but opacity
+    pub(crate) fn color_filter(&self) -> Option<ColorFilter> {
+        Some(color_filters::matrix_row_major(&self.matrix()?, None))
+    }
+}
+
+/// `outer` applied after `inner`
+fn concat(outer: &ColorMatrix, inner: &ColorMatrix) -> ColorMatrix {
+    let mut result = [[0.0; 5]; 4];
+    for (row, result_row) in result.iter_mut().enumerate() {
+        for (column, value) in result_row.iter_mut().enumerate() {
+            *value = (0..4).map(|k| outer[row][k] * inner[k][column]).sum();
+        }
+        result_row[4] += outer[row][4];
+    }
+    result
+}
+
+/// An adjustment easing into the overlay's current one
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct AdjustmentTransition {
+    from: ColorAdjustment,
+    start: Instant,
+    duration: Duration,
+}
+
+impl AdjustmentTransition {
+    /// Eased progress from 0 to 1
+    fn progress(&self, now: Instant) -> f32 {
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+
+        let t = (now.saturating_duration_since(self.start).as_secs_f32() / self.duration.as_secs_f32()).min(1.0);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+impl Overlay {
+    // COLOR ADJUSTMENT FUNCTIONS -----------------
+
+    /// Replaces the overlay wide adjustment right away, stopping any running animation
+    pub fn set_color_adjustment(&mut self, adjustment: ColorAdjustment) {
+        self.color_adjustment = adjustment;
+        self.adjustment_transition = None;
+    }
+
+    /// Eases from the current adjustment to `adjustment` over `duration`, advanced every `begin_scene`
+    pub fn animate_color_adjustment(&mut self, adjustment: ColorAdjustment, duration: Duration) {
+        let from = self.color_adjustment();
+        self.color_adjustment = adjustment;
+        self.adjustment_transition = Some(AdjustmentTransition { from, start: Instant::now(), duration });
+    }
+
+    /// The adjustment as it's applied right now, part way through an animation if one is running
+    pub fn color_adjustment(&self) -> ColorAdjustment {
+        match &self.adjustment_transition {
+            Some(transition) => transition.from.lerp(&self.color_adjustment, transition.progress(Instant::now())),
+            None => self.color_adjustment,
+        }
+    }
+
+    /// Dims the whole overlay, e.g. to 0.5 while the game is paused
+    pub fn set_master_opacity(&mut self, opacity: f32) {
+        let adjustment = ColorAdjustment { opacity, ..self.color_adjustment };
+        self.set_color_adjustment(adjustment);
+    }
+
+    /// Fades the master opacity to `opacity` over `duration`
+    pub fn fade_master_opacity(&mut self, opacity: f32, duration: Duration) {
+        let adjustment = ColorAdjustment { opacity, ..self.color_adjustment };
+        self.animate_color_adjustment(adjustment, duration);
+    }
+
+    /// Paint for the layer a frame is drawn into, `None` when the frame can be drawn directly
+    pub(crate) fn compositing_paint(&mut self) -> Option<Paint> {
+        let adjustment = self.color_adjustment();
+        if let Some(transition) = &self.adjustment_transition {
+            if transition.progress(Instant::now()) >= 1.0 {
+                self.adjustment_transition = None;
+            }
+        }
+
+        // Color vision filters go last, they describe how the viewer sees the adjusted result
+        let color_filter = match (adjustment.color_filter(), self.color_vision.color_filter()) {
+            (Some(adjust), Some(vision)) => vision.composed(adjust),
+            (adjust, vision) => adjust.or(vision),
+        };
+
+        if color_filter.is_none() && adjustment.opacity >= 1.0 {
+            return None;
+        }
+
+        let mut paint = Paint::default();
+        paint.set_alpha_f(adjustment.opacity.clamp(0.0, 1.0));
+        if let Some(color_filter) = color_filter {
+            paint.set_color_filter(color_filter);
+        }
+        Some(paint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_adjustment() {
+        let orange = Color::rgb(230, 120, 20);
+        assert_eq!(ColorAdjustment::IDENTITY.apply(orange), orange);
+        assert_eq!(ColorAdjustment::opacity(0.5).apply(orange), orange.with_alpha(128));
+
+        // No saturation leaves equal channels
+        let grey = ColorAdjustment { saturation: 0.0, ..Default::default() }.apply(orange);
+        assert!(grey.r.abs_diff(grey.g) <= 1 && grey.g.abs_diff(grey.b) <= 1);
+
+        // No contrast flattens everything to mid grey, brightness shifts it
+        let flat = ColorAdjustment { contrast: 0.0, brightness: 0.25, ..Default::default() };
+        assert_eq!(flat.apply(orange), flat.apply(Color::BLUE));
+        assert_eq!(flat.apply(orange).r, 191);
+
+        // A full tint turns white into the tint color
+        let tint = ColorAdjustment { tint: Color::rgb(0, 128, 255), ..Default::default() };
+        assert_eq!(tint.apply(Color::WHITE), Color::rgb(0, 128, 255));
+
+        let halfway = ColorAdjustment::IDENTITY.lerp(&tint, 0.5);
+        assert_eq!(halfway.tint, Color::rgba(0, 128, 255, 128));
+    }
+}
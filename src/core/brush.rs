@@ -0,0 +1,153 @@
+use skia_safe::{Color4f, ColorSpace, Paint, Point, Rect, Shader, TileMode};
+use skia_safe::gradient_shader::{interpolation, Interpolation};
+use crate::core::{Color, HueDirection, InterpolationSpace, helper::to_color_4f};
+
+/// How a shape or glyph is filled. Gradients stretch over the bounds of whatever is drawn.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Brush {
+    Solid(Color),
+    /// Evenly spaced colors from the left edge to the right edge, or top to bottom when vertical
+    LinearGradient {
+        colors: Vec<Color>,
+        is_vertical: bool,
+        space: InterpolationSpace,
+    },
+    /// Evenly spaced colors from the center outwards, reaching the last color at the farthest corner
+    RadialGradient {
+        colors: Vec<Color>,
+        space: InterpolationSpace,
+    },
+}
+
+impl Brush {
+    pub fn linear_gradient(color1: impl Into<Color>, color2: impl Into<Color>, is_vertical: bool) -> Self {
+        Brush::LinearGradient {
+            colors: vec![color1.into(), color2.into()],
+            is_vertical,
+            space: InterpolationSpace::default(),
+        }
+    }
+
+    pub fn radial_gradient(color1: impl Into<Color>, color2: impl Into<Color>) -> Self {
+        Brush::RadialGradient { colors: vec![color1.into(), color2.into()], space: InterpolationSpace::default() }
+    }
+
+    /// Sets the color space a gradient blends in, solid brushes are unchanged
+    pub fn with_interpolation(mut self, new_space: InterpolationSpace) -> Self {
+        match &mut self {
+            Brush::Solid(_) => {}
+            Brush::LinearGradient { space, .. } | Brush::RadialGradient { space, .. } => *space = new_space,
+        }
+        self
+    }
+
+    /// A single color standing in for the brush, the average of a gradient's colors
+    pub fn average_color(&self) -> Color {
+        match self {
+            Brush::Solid(color) => *color,
+            Brush::LinearGradient { colors, .. } | Brush::RadialGradient { colors, .. } => {
+                let mut sum = [0.0; 4];
+                for color in colors {
+                    for (total, channel) in sum.iter_mut().zip(color.to_f32()) {
+                        *total += channel;
+                    }
+                }
+                Color::from_f32(sum.map(|total| total / colors.len().max(1) as f32))
+            }
+        }
+    }
+
+    /// Creates an anti-aliased fill paint for something occupying `bounds`
+    pub(crate) fn to_paint(&self, bounds: Rect) -> Paint {
+        let mut paint = Paint::default();
+
+        match self {
+            Brush::Solid(color) => {
+                paint = Paint::new(to_color_4f(*color), None);
+            }
+            Brush::LinearGradient { colors, is_vertical, space } => {
+                let end = if *is_vertical {
+                    Point::new(bounds.left, bounds.bottom)
+                } else {
+                    Point::new(bounds.right, bounds.top)
+                };
+
+                paint.set_shader(linear_gradient_shader((Point::new(bounds.left, bounds.top), end), colors, *space));
+            }
+            Brush::RadialGradient { colors, space } => {
+                let radius = (bounds.width() * bounds.width() + bounds.height() * bounds.height()).sqrt() / 2.0;
+                paint.set_shader(radial_gradient_shader(bounds.center(), radius, colors, *space));
+            }
+        }
+
+        paint.set_anti_alias(true);
+        paint
+    }
+}
+
+/// Skia's gradient interpolation settings for a color space. Colors are given in sRGB either way.
+fn gradient_interpolation(space: InterpolationSpace) -> Interpolation {
+    let (color_space, hue_method) = match space {
+        InterpolationSpace::Srgb => (interpolation::ColorSpace::SRGB, interpolation::HueMethod::Shorter),
+        InterpolationSpace::LinearSrgb => (interpolation::ColorSpace::SRGBLinear, interpolation::HueMethod::Shorter),
+        InterpolationSpace::Oklab => (interpolation::ColorSpace::OKLab, interpolation::HueMethod::Shorter),
+        InterpolationSpace::Oklch(direction) => (interpolation::ColorSpace::OKLCH, match direction {
+            HueDirection::Shorter => interpolation::HueMethod::Shorter,
+            HueDirection::Longer => interpolation::HueMethod::Longer,
+            HueDirection::Increasing => interpolation::HueMethod::Increasing,
+            HueDirection::Decreasing => interpolation::HueMethod::Decreasing,
+        }),
+    };
+
+    Interpolation { in_premul: interpolation::InPremul::No, color_space, hue_method }
+}
+
+/// Evenly spaced linear gradient between two points, blended in `space`
+pub(crate) fn linear_gradient_shader(
+    points: (Point, Point),
+    colors: &[Color],
+    space: InterpolationSpace
+) -> Option<Shader> {
+    let colors: Vec<Color4f> = colors.iter().map(|color| to_color_4f(*color)).collect();
+
+    Shader::linear_gradient_with_interpolation(
+        points,
+        (colors.as_slice(), ColorSpace::new_srgb()),
+        None,
+        TileMode::Clamp,
+        gradient_interpolation(space),
+        None,
+    )
+}
+
+/// Evenly spaced radial gradient from `center` to `radius`, blended in `space`
+pub(crate) fn radial_gradient_shader(
+    center: Point,
+    radius: f32,
+    colors: &[Color],
+    space: InterpolationSpace
+) -> Option<Shader> {
+    let colors: Vec<Color4f> = colors.iter().map(|color| to_color_4f(*color)).collect();
+
+    Shader::radial_gradient_with_interpolation(
+        (center, radius),
+        (colors.as_slice(), ColorSpace::new_srgb()),
+        None,
+        TileMode::Clamp,
+        gradient_interpolation(space),
+        None,
+    )
+}
+
+impl From<Color> for Brush {
+    fn from(color: Color) -> Self {
+        Brush::Solid(color)
+    }
+}
+
+impl From<(u8, u8, u8, u8)> for Brush {
+    fn from(color: (u8, u8, u8, u8)) -> Self {
+        Brush::Solid(color.into())
+    }
+}
@@ -0,0 +1,735 @@
+use std::fmt;
+use std::str::FromStr;
+use skia_safe::Color4f;
+
+/// An 8-bit per channel, non-premultiplied sRGB color.
+/// Every draw function takes `impl Into<Color>`, so tuples, hex strings via `parse`, `0xRRGGBBAA` integers,
+/// float arrays and Skia colors all work.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+/// Which way around the hue circle OKLCH interpolation travels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HueDirection {
+    /// The smaller arc, never more than 180 degrees
+    #[default]
+    Shorter,
+    /// The larger arc, red to blue through green and yellow
+    Longer,
+    /// Hue only goes up, wrapping at 360
+    Increasing,
+    /// Hue only goes down, wrapping at 0
+    Decreasing,
+}
+
+/// Color space two colors are blended in, for gradients and `Color::lerp_in`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InterpolationSpace {
+    /// Blends the stored sRGB values directly. Cheap, but midpoints come out dark and muddy.
+    #[default]
+    Srgb,
+    /// Blends physical light intensity, midpoints are brighter than `Srgb`
+    LinearSrgb,
+    /// Perceptually even lightness and hue, usually the best looking two-color blend
+    Oklab,
+    /// OKLab in polar form, keeps colors saturated by going around the hue circle
+    Oklch(HueDirection),
+}
+
+/// Why a string couldn't be turned into a `Color`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorParseError {
+    pub input: String,
+}
+
+impl fmt::Display for ColorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}' is not a hex color or CSS color name", self.input)
+    }
+}
+
+impl std::error::Error for ColorParseError {}
+
+impl Color {
+    pub const TRANSPARENT: Color = Color::rgba(0, 0, 0, 0);
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+    pub const RED: Color = Color::rgb(255, 0, 0);
+    /// Full intensity green, named like CSS where `green` is the darker #008000
+    pub const LIME: Color = Color::rgb(0, 255, 0);
+    pub const BLUE: Color = Color::rgb(0, 0, 255);
+    pub const YELLOW: Color = Color::rgb(255, 255, 0);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Parses `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa`. Only this call accepts hex without the `#`,
+    /// `str::parse` and themes require it so words like `cafe` aren't read as colors.
+    pub fn from_hex(hex: &str) -> Result<Self, ColorParseError> {
+        let error = || ColorParseError { input: hex.to_string() };
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+
+        if !digits.is_ascii() || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(error());
+        }
+
+        let channel = |index: usize, width: usize| {
+            let value = u8::from_str_radix(&digits[index * width..(index + 1) * width], 16).unwrap_or(0);
+            // Short forms repeat each digit, so #f80 is #ff8800
+            if width == 1 { value * 17 } else { value }
+        };
+
+        match digits.len() {
+            3 => Ok(Self::rgb(channel(0, 1), channel(1, 1), channel(2, 1))),
+            4 => Ok(Self::rgba(channel(0, 1), channel(1, 1), channel(2, 1), channel(3, 1))),
+            6 => Ok(Self::rgb(channel(0, 2), channel(1, 2), channel(2, 2))),
+            8 => Ok(Self::rgba(channel(0, 2), channel(1, 2), channel(2, 2), channel(3, 2))),
+            _ => Err(error()),
+        }
+    }
+
+    /// Looks up one of the CSS named colors, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        if name == "transparent" {
+            return Some(Self::TRANSPARENT);
+        }
+
+        CSS_COLORS.binary_search_by(|(css_name, _)| css_name.cmp(&name.as_str()))
+            .ok()
+            .map(|index| Self::from_rgb_u32(CSS_COLORS[index].1))
+    }
+
+    /// Hue in degrees, saturation and lightness from 0 to 1
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Self {
+        Self::from_hsla(hue, saturation, lightness, 1.0)
+    }
+
+    pub fn from_hsla(hue: f32, saturation: f32, lightness: f32, alpha: f32) -> Self {
+        let saturation = saturation.clamp(0.0, 1.0);
+        let lightness = lightness.clamp(0.0, 1.0);
+
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let (r, g, b) = hue_to_rgb(hue, chroma);
+        let m = lightness - chroma / 2.0;
+
+        Self::from_f32([r + m, g + m, b + m, alpha])
+    }
+
+    /// Hue in degrees, saturation and value from 0 to 1
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        Self::from_hsva(hue, saturation, value, 1.0)
+    }
+
+    pub fn from_hsva(hue: f32, saturation: f32, value: f32, alpha: f32) -> Self {
+        let saturation = saturation.clamp(0.0, 1.0);
+        let value = value.clamp(0.0, 1.0);
+
+        let chroma = value * saturation;
+        let (r, g, b) = hue_to_rgb(hue, chroma);
+        let m = value - chroma;
+
+        Self::from_f32([r + m, g + m, b + m, alpha])
+    }
+
+    /// Channels from 0 to 1, out of range values are clamped
+    pub fn from_f32([r, g, b, a]: [f32; 4]) -> Self {
+        let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        Self::rgba(channel(r), channel(g), channel(b), channel(a))
+    }
+
+    /// Channels from 0 to 1
+    pub fn to_f32(self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a].map(|channel| channel as f32 / 255.0)
+    }
+
+    /// Hue in degrees, saturation and lightness from 0 to 1
+    pub fn to_hsl(self) -> (f32, f32, f32) {
+        let (hue, min, max) = self.hue_min_max();
+        let lightness = (max + min) / 2.0;
+        let saturation = if max == min { 0.0 } else { (max - min) / (1.0 - (2.0 * lightness - 1.0).abs()) };
+
+        (hue, saturation, lightness)
+    }
+
+    /// Hue in degrees, saturation and value from 0 to 1
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let (hue, min, max) = self.hue_min_max();
+        let saturation = if max == 0.0 { 0.0 } else { (max - min) / max };
+
+        (hue, saturation, max)
+    }
+
+    pub fn with_alpha(self, alpha: u8) -> Self {
+        Self { a: alpha, ..self }
+    }
+
+    /// Scales the alpha, e.g. by 0.5 to fade a color to half its opacity
+    pub fn multiply_alpha(self, factor: f32) -> Self {
+        let alpha = (self.a as f32 * factor.clamp(0.0, 1.0)).round() as u8;
+        self.with_alpha(alpha)
+    }
+
+    /// Raises the HSL lightness by `amount` (0 to 1)
+    pub fn lighten(self, amount: f32) -> Self {
+        let (hue, saturation, lightness) = self.to_hsl();
+        Self::from_hsla(hue, saturation, lightness + amount, self.a as f32 / 255.0)
+    }
+
+    /// Lowers the HSL lightness by `amount` (0 to 1)
+    pub fn darken(self, amount: f32) -> Self {
+        self.lighten(-amount)
+    }
+
+    /// Color channels multiplied by alpha, the form Skia blends in
+    pub fn premultiplied(self) -> Self {
+        let scale = |channel: u8| ((channel as u16 * self.a as u16 + 127) / 255) as u8;
+        Self::rgba(scale(self.r), scale(self.g), scale(self.b), self.a)
+    }
+
+    /// This color painted over `backdrop` with normal source-over blending
+    pub fn over(self, backdrop: Color) -> Self {
+        let [r, g, b, a] = self.to_f32();
+        let [br, bg, bb, ba] = backdrop.to_f32();
+        let alpha = a + ba * (1.0 - a);
+        if alpha <= 0.0 {
+            return Self::TRANSPARENT;
+        }
+
+        let blend = |channel: f32, backdrop: f32| (channel * a + backdrop * ba * (1.0 - a)) / alpha;
+        Self::from_f32([blend(r, br), blend(g, bg), blend(b, bb), alpha])
+    }
+
+    /// Blends towards `other` in sRGB, `t` from 0 (self) to 1 (other)
+    pub fn lerp(self, other: Color, t: f32) -> Self {
+        self.lerp_in(other, InterpolationSpace::Srgb, t)
+    }
+
+    /// Blends towards `other` in the given color space, `t` from 0 (self) to 1 (other).
+    /// Matches how gradients drawn with the same space look at position `t`.
+    pub fn lerp_in(self, other: Color, space: InterpolationSpace, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let [r1, g1, b1, a1] = self.to_f32();
+        let [r2, g2, b2, a2] = other.to_f32();
+        let alpha = lerp(a1, a2, t);
+
+        let rgb = match space {
+            InterpolationSpace::Srgb => [lerp(r1, r2, t), lerp(g1, g2, t), lerp(b1, b2, t)],
+            InterpolationSpace::LinearSrgb => {
+                let from = [r1, g1, b1].map(srgb_to_linear);
+                let to = [r2, g2, b2].map(srgb_to_linear);
+                [0, 1, 2].map(|i| linear_to_srgb(lerp(from[i], to[i], t)))
+            }
+            InterpolationSpace::Oklab => {
+                let from = linear_to_oklab([r1, g1, b1].map(srgb_to_linear));
+                let to = linear_to_oklab([r2, g2, b2].map(srgb_to_linear));
+                oklab_to_linear([0, 1, 2].map(|i| lerp(from[i], to[i], t))).map(linear_to_srgb)
+            }
+            InterpolationSpace::Oklch(direction) => {
+                let from = oklab_to_oklch(linear_to_oklab([r1, g1, b1].map(srgb_to_linear)));
+                let to = oklab_to_oklch(linear_to_oklab([r2, g2, b2].map(srgb_to_linear)));
+                let hue = lerp_hue(from, to, direction, t);
+                let lch = [lerp(from[0], to[0], t), lerp(from[1], to[1], t), hue];
+                oklab_to_linear(oklch_to_oklab(lch)).map(linear_to_srgb)
+            }
+        };
+
+        Self::from_f32([rgb[0], rgb[1], rgb[2], alpha])
+    }
+
+    /// WCAG relative luminance, 0 for black to 1 for white. Alpha is ignored.
+    pub fn relative_luminance(self) -> f32 {
+        let [r, g, b, _] = self.to_f32().map(srgb_to_linear);
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    }
+
+    /// WCAG contrast ratio, 1 for identical luminance up to 21 for black on white. Order doesn't matter.
+    /// WCAG asks for at least 4.5 for normal text and 3 for large text.
+    pub fn contrast_ratio(self, other: Color) -> f32 {
+        let (a, b) = (self.relative_luminance(), other.relative_luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    /// Whichever of two colors reads better on this one, e.g. a theme's light and dark text colors
+    pub fn pick_contrasting(self, first: Color, second: Color) -> Color {
+        if self.contrast_ratio(second) > self.contrast_ratio(first) { second } else { first }
+    }
+
+    /// Black or white, whichever reads better on this color
+    pub fn contrasting_text(self) -> Color {
+        self.pick_contrasting(Color::WHITE, Color::BLACK)
+    }
+
+    /// This color, lightened or darkened in OKLab by as little as possible to reach `target` contrast
+    /// against `background`. Falls back to black or white when no shade of it gets there.
+    pub fn with_min_contrast(self, background: Color, target: f32) -> Self {
+        if self.contrast_ratio(background) >= target {
+            return self;
+        }
+
+        // Smallest blend towards white and towards black that reaches the target
+        let candidates = [Color::WHITE, Color::BLACK].map(|extreme| {
+            let extreme = extreme.with_alpha(self.a);
+            if extreme.contrast_ratio(background) < target {
+                return None;
+            }
+
+            let (mut low, mut high) = (0.0, 1.0);
+            for _ in 0..16 {
+                let mid = (low + high) / 2.0;
+                if self.lerp_in(extreme, InterpolationSpace::Oklab, mid).contrast_ratio(background) >= target {
+                    high = mid;
+                } else {
+                    low = mid;
+                }
+            }
+            Some((high, self.lerp_in(extreme, InterpolationSpace::Oklab, high)))
+        });
+
+        candidates.into_iter()
+            .flatten()
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, color)| color)
+            .unwrap_or_else(|| background.contrasting_text().with_alpha(self.a))
+    }
+
+    /// `#rrggbbaa`
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+    }
+
+    fn from_rgb_u32(rgb: u32) -> Self {
+        Self::rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+    }
+
+    fn hue_min_max(self) -> (f32, f32, f32) {
+        let [r, g, b, _] = self.to_f32();
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+
+        let hue = if delta == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+
+        (hue, min, max)
+    }
+}
+
+/// RGB of a fully saturated hue scaled to `chroma`, before the lightness/value offset is added
+fn hue_to_rgb(hue: f32, chroma: f32) -> (f32, f32, f32) {
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+
+    match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    }
+}
+
+pub(crate) fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+pub(crate) fn srgb_to_linear(channel: f32) -> f32 {
+    if channel <= 0.04045 { channel / 12.92 } else { ((channel + 0.055) / 1.055).powf(2.4) }
+}
+
+pub(crate) fn linear_to_srgb(channel: f32) -> f32 {
+    if channel <= 0.0031308 { channel * 12.92 } else { 1.055 * channel.powf(1.0 / 2.4) - 0.055 }
+}
+
+/// Linear sRGB to OKLab, as defined by Björn Ottosson
+#[allow(clippy::excessive_precision)]
+fn linear_to_oklab([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+    let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+    let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+
+    [
+        0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+    ]
+}
+
+#[allow(clippy::excessive_precision)]
+fn oklab_to_linear([lightness, a, b]: [f32; 3]) -> [f32; 3] {
+    let l = (lightness + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+    let m = (lightness - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+    let s = (lightness - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+
+    [
+        4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+        -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+        -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+    ]
+}
+
+/// Lightness, chroma and hue in degrees
+fn oklab_to_oklch([lightness, a, b]: [f32; 3]) -> [f32; 3] {
+    [lightness, a.hypot(b), b.atan2(a).to_degrees().rem_euclid(360.0)]
+}
+
+fn oklch_to_oklab([lightness, chroma, hue]: [f32; 3]) -> [f32; 3] {
+    let (sin, cos) = hue.to_radians().sin_cos();
+    [lightness, chroma * cos, chroma * sin]
+}
+
+/// Hue between two OKLCH colors. Greys have no meaningful hue, so they take the other color's.
+fn lerp_hue(from: [f32; 3], to: [f32; 3], direction: HueDirection, t: f32) -> f32 {
+    const ACHROMATIC: f32 = 1e-4;
+
+    let (from_hue, to_hue) = match (from[1] < ACHROMATIC, to[1] < ACHROMATIC) {
+        (true, false) => (to[2], to[2]),
+        (false, true) => (from[2], from[2]),
+        _ => (from[2], to[2]),
+    };
+
+    let difference = to_hue - from_hue;
+    let delta = match direction {
+        HueDirection::Shorter => (difference + 180.0).rem_euclid(360.0) - 180.0,
+        HueDirection::Longer => {
+            let shorter = (difference + 180.0).rem_euclid(360.0) - 180.0;
+            if shorter == 0.0 { 0.0 } else { shorter - 360.0 * shorter.signum() }
+        }
+        HueDirection::Increasing => difference.rem_euclid(360.0),
+        HueDirection::Decreasing => -(-difference).rem_euclid(360.0),
+    };
+
+    (from_hue + delta * t).rem_euclid(360.0)
+}
+
+impl FromStr for Color {
+    type Err = ColorParseError;
+
+    /// Accepts CSS color names and `#` prefixed hex colors, so words made of hex digits like `cafe` aren't colors
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(color) = Self::from_name(s) {
+            return Ok(color);
+        }
+
+        if !s.starts_with('#') {
+            return Err(ColorParseError { input: s.to_string() });
+        }
+        Self::from_hex(s)
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl From<(u8, u8, u8, u8)> for Color {
+    fn from((r, g, b, a): (u8, u8, u8, u8)) -> Self {
+        Self::rgba(r, g, b, a)
+    }
+}
+
+impl From<(u8, u8, u8)> for Color {
+    fn from((r, g, b): (u8, u8, u8)) -> Self {
+        Self::rgb(r, g, b)
+    }
+}
+
+/// `0xRRGGBBAA`, the same order as hex strings
+impl From<u32> for Color {
+    fn from(rgba: u32) -> Self {
+        let [r, g, b, a] = rgba.to_be_bytes();
+        Self::rgba(r, g, b, a)
+    }
+}
+
+impl From<[f32; 4]> for Color {
+    fn from(rgba: [f32; 4]) -> Self {
+        Self::from_f32(rgba)
+    }
+}
+
+impl From<skia_safe::Color> for Color {
+    fn from(color: skia_safe::Color) -> Self {
+        Self::rgba(color.r(), color.g(), color.b(), color.a())
+    }
+}
+
+impl From<Color4f> for Color {
+    fn from(color: Color4f) -> Self {
+        Self::from_f32([color.r, color.g, color.b, color.a])
+    }
+}
+
+impl From<Color> for (u8, u8, u8, u8) {
+    fn from(color: Color) -> Self {
+        (color.r, color.g, color.b, color.a)
+    }
+}
+
+impl From<Color> for skia_safe::Color {
+    fn from(color: Color) -> Self {
+        skia_safe::Color::from_argb(color.a, color.r, color.g, color.b)
+    }
+}
+
+impl From<Color> for Color4f {
+    fn from(color: Color) -> Self {
+        let [r, g, b, a] = color.to_f32();
+        Color4f::new(r, g, b, a)
+    }
+}
+
+/// CSS named colors as `0xRRGGBB`, sorted by name for binary search
+const CSS_COLORS: &[(&str, u32)] = &[
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex_and_names() {
+        assert_eq!("#f80".parse(), Ok(Color::rgb(255, 136, 0)));
+        assert_eq!("#ff880080".parse(), Ok(Color::rgba(255, 136, 0, 128)));
+        assert_eq!("RebeccaPurple".parse(), Ok(Color::rgb(102, 51, 153)));
+        assert_eq!(Color::from(0x11223344), Color::rgba(0x11, 0x22, 0x33, 0x44));
+        assert!("#12345".parse::<Color>().is_err());
+        assert!("notacolor".parse::<Color>().is_err());
+        assert!("cafe".parse::<Color>().is_err());
+        assert!("bad".parse::<Color>().is_err());
+        assert_eq!("green".parse(), Ok(Color::rgb(0, 128, 0)));
+        assert_eq!("lime".parse(), Ok(Color::LIME));
+        assert_eq!(Color::from_hex("cafe"), Ok(Color::rgba(204, 170, 255, 238)));
+    }
+
+    #[test]
+    fn test_hsl_hsv_round_trip() {
+        let orange = Color::rgb(255, 136, 0);
+        let (h, s, l) = orange.to_hsl();
+        assert_eq!(Color::from_hsl(h, s, l), orange);
+
+        let (h, s, v) = orange.to_hsv();
+        assert_eq!(Color::from_hsv(h, s, v), orange);
+
+        assert_eq!(Color::from_hsl(120.0, 1.0, 0.5), Color::LIME);
+        assert_eq!(Color::WHITE.darken(1.0), Color::BLACK);
+    }
+
+    #[test]
+    fn test_lerp_in() {
+        let red = Color::RED;
+        let blue = Color::BLUE;
+
+        assert_eq!(red.lerp(blue, 0.5), Color::rgb(128, 0, 128));
+        assert_eq!(red.lerp_in(blue, InterpolationSpace::Oklab, 0.0), red);
+        assert_eq!(red.lerp_in(blue, InterpolationSpace::Oklab, 1.0), blue);
+
+        // Perceptual spaces keep the midpoint brighter than plain sRGB
+        let srgb_mid = red.lerp(blue, 0.5).to_hsl().2;
+        let oklab_mid = red.lerp_in(blue, InterpolationSpace::Oklab, 0.5).to_hsl().2;
+        let linear_mid = red.lerp_in(blue, InterpolationSpace::LinearSrgb, 0.5).to_hsl().2;
+        assert!(oklab_mid > srgb_mid);
+        assert!(linear_mid > srgb_mid);
+
+        // Red (hue ~29) to blue (hue ~264) the short way passes through magenta, the long way through green
+        let shorter = red.lerp_in(blue, InterpolationSpace::Oklch(HueDirection::Shorter), 0.5);
+        let longer = red.lerp_in(blue, InterpolationSpace::Oklch(HueDirection::Longer), 0.5);
+        assert!(shorter.r > shorter.g && shorter.b > shorter.g);
+        assert!(longer.g > longer.b);
+    }
+
+    #[test]
+    fn test_contrast() {
+        assert!((Color::BLACK.contrast_ratio(Color::WHITE) - 21.0).abs() < 0.01);
+        assert_eq!(Color::RED.contrast_ratio(Color::RED), 1.0);
+        assert_eq!(Color::YELLOW.contrasting_text(), Color::BLACK);
+        assert_eq!(Color::rgb(0, 0, 128).contrasting_text(), Color::WHITE);
+
+        // Yellow on white gets darkened just enough and stays yellowish
+        let text = Color::YELLOW.with_min_contrast(Color::WHITE, 4.5);
+        assert!(text.contrast_ratio(Color::WHITE) >= 4.5);
+        assert!(text.contrast_ratio(Color::WHITE) < 5.0);
+        assert!(text.r > text.b && text.g > text.b);
+        assert_eq!(Color::BLACK.with_min_contrast(Color::WHITE, 4.5), Color::BLACK);
+    }
+
+    #[test]
+    fn test_alpha() {
+        assert_eq!(Color::rgba(200, 100, 50, 128).premultiplied(), Color::rgba(100, 50, 25, 128));
+        assert_eq!(Color::WHITE.multiply_alpha(0.5).a, 128);
+        assert_eq!(Color::rgba(255, 255, 255, 128).over(Color::BLACK), Color::rgb(128, 128, 128));
+        assert_eq!(Color::RED.over(Color::BLUE), Color::RED);
+        assert_eq!(Color::TRANSPARENT.over(Color::TRANSPARENT), Color::TRANSPARENT);
+    }
+}
@@ -0,0 +1,249 @@
+use skia_safe::{ColorFilter, ColorSpace, color_filters};
+use crate::core::{
+    Color, Overlay,
+    color::{linear_to_srgb, srgb_to_linear}
+};
+
+/// Categorical palettes whose colors stay distinguishable with the common color vision deficiencies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Palette {
+    /// Okabe and Ito's 8 colors, black is last so cycling starts with colors that show on dark scenes
+    #[default]
+    OkabeIto,
+    /// Paul Tol's bright scheme, 7 colors
+    TolBright,
+    /// Paul Tol's muted scheme, 9 colors
+    TolMuted,
+    /// IBM Design's 5 color accessible palette
+    Ibm,
+}
+
+const OKABE_ITO: [Color; 8] = [
+    Color::rgb(0xE6, 0x9F, 0x00), // orange
+    Color::rgb(0x56, 0xB4, 0xE9), // sky blue
+    Color::rgb(0x00, 0x9E, 0x73), // bluish green
+    Color::rgb(0xF0, 0xE4, 0x42), // yellow
+    Color::rgb(0x00, 0x72, 0xB2), // blue
+    Color::rgb(0xD5, 0x5E, 0x00), // vermillion
+    Color::rgb(0xCC, 0x79, 0xA7), // reddish purple
+    Color::rgb(0x00, 0x00, 0x00), // black
+];
+
+const TOL_BRIGHT: [Color; 7] = [
+    Color::rgb(0x44, 0x77, 0xAA),
+    Color::rgb(0xEE, 0x66, 0x77),
+    Color::rgb(0x22, 0x88, 0x33),
+    Color::rgb(0xCC, 0xBB, 0x44),
+    Color::rgb(0x66, 0xCC, 0xEE),
+    Color::rgb(0xAA, 0x33, 0x77),
+    Color::rgb(0xBB, 0xBB, 0xBB),
+];
+
+const TOL_MUTED: [Color; 9] = [
+    Color::rgb(0xCC, 0x66, 0x77),
+    Color::rgb(0x33, 0x22, 0x88),
+    Color::rgb(0xDD, 0xCC, 0x77),
+    Color::rgb(0x11, 0x77, 0x33),
+    Color::rgb(0x88, 0xCC, 0xEE),
+    Color::rgb(0x88, 0x22, 0x55),
+    Color::rgb(0x44, 0xAA, 0x99),
+    Color::rgb(0x99, 0x99, 0x33),
+    Color::rgb(0xAA, 0x44, 0x99),
+];
+
+const IBM: [Color; 5] = [
+    Color::rgb(0x64, 0x8F, 0xFF),
+    Color::rgb(0x78, 0x5E, 0xF0),
+    Color::rgb(0xDC, 0x26, 0x7F),
+    Color::rgb(0xFE, 0x61, 0x00),
+    Color::rgb(0xFF, 0xB0, 0x00),
+];
+
+impl Palette {
+    /// Replacements for red/green status colors: good, warning and bad from Okabe-Ito
+    pub const GOOD: Color = OKABE_ITO[4];
+    pub const WARNING: Color = OKABE_ITO[0];
+    pub const BAD: Color = OKABE_ITO[5];
+
+    pub fn colors(self) -> &'static [Color] {
+        match self {
+            Palette::OkabeIto => &OKABE_ITO,
+            Palette::TolBright => &TOL_BRIGHT,
+            Palette::TolMuted => &TOL_MUTED,
+            Palette::Ibm => &IBM,
+        }
+    }
+
+    /// The color for a category, wrapping around when there are more categories than colors
+    pub fn color(self, index: usize) -> Color {
+        let colors = self.colors();
+        colors[index % colors.len()]
+    }
+}
+
+/// A type of dichromacy, missing one of the three cone types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorVisionDeficiency {
+    /// No red cones, reds look dark and close to greens
+    Protanopia,
+    /// No green cones, the most common form of red-green color blindness
+    Deuteranopia,
+    /// No blue cones, blues and greens as well as yellows and pinks get confused
+    Tritanopia,
+}
+
+impl ColorVisionDeficiency {
+    /// Machado et al. 2009 simulation matrices at full severity, for linear RGB
+    fn simulation_matrix(self) -> [[f32; 3]; 3] {
+        match self {
+            ColorVisionDeficiency::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            ColorVisionDeficiency::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            ColorVisionDeficiency::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        }
+    }
+
+    /// Where daltonizing moves the color information the viewer can't see
+    fn error_shift_matrix(self) -> [[f32; 3]; 3] {
+        match self {
+            // Lost red-green contrast is shown as lightness and blue instead
+            ColorVisionDeficiency::Protanopia | ColorVisionDeficiency::Deuteranopia => [
+                [0.0, 0.0, 0.0],
+                [0.7, 1.0, 0.0],
+                [0.7, 0.0, 1.0],
+            ],
+            // Lost blue-yellow contrast is shown as red and green instead
+            ColorVisionDeficiency::Tritanopia => [
+                [1.0, 0.0, 0.7],
+                [0.0, 1.0, 0.7],
+                [0.0, 0.0, 0.0],
+            ],
+        }
+    }
+}
+
+/// Post-processing applied to the whole overlay when a frame ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorVisionFilter {
+    #[default]
+    None,
+    /// Shows the overlay the way a viewer with the deficiency would see it
+    Simulate(ColorVisionDeficiency),
+    /// Shifts colors so viewers with the deficiency can tell apart what they otherwise couldn't
+    Daltonize(ColorVisionDeficiency),
+}
+
+impl ColorVisionFilter {
+    /// The 3x3 linear RGB matrix the filter applies, `None` when it doesn't change colors
+    pub fn matrix(self) -> Option<[[f32; 3]; 3]> {
+        match self {
+            ColorVisionFilter::None => None,
+            ColorVisionFilter::Simulate(deficiency) => Some(deficiency.simulation_matrix()),
+            ColorVisionFilter::Daltonize(deficiency) => {
+                // corrected = rgb + shift * (rgb - simulated), folded into one matrix
+                let simulation = deficiency.simulation_matrix();
+                let shift = deficiency.error_shift_matrix();
+
+                let mut matrix = [[0.0; 3]; 3];
+                for (row, matrix_row) in matrix.iter_mut().enumerate() {
+                    for (column, value) in matrix_row.iter_mut().enumerate() {
+                        let identity = |r: usize, c: usize| if r == c { 1.0 } else { 0.0 };
+                        let shifted_error: f32 = (0..3)
+                            .map(|k| shift[row][k] * (identity(k, column) - simulation[k][column]))
+                            .sum();
+                        *value = identity(row, column) + shifted_error;
+                    }
+                }
+                Some(matrix)
+            }
+        }
+    }
+
+    /// Applies the filter to a single color, matching what the overlay filter does to pixels
+    pub fn apply(self, color: Color) -> Color {
+        let Some(matrix) = self.matrix() else {
+            return color;
+        };
+
+        let [r, g, b, a] = color.to_f32();
+        let linear = [r, g, b].map(srgb_to_linear);
+        let [r, g, b] = matrix.map(|row| {
+            let value: f32 = row.iter().zip(linear).map(|(weight, channel)| weight * channel).sum();
+            linear_to_srgb(value.clamp(0.0, 1.0))
+        });
+
+        Color::from_f32([r, g, b, a])
+    }
+
+    /// Skia color filter doing the matrix math in linear sRGB
+    pub(crate) fn color_filter(self) -> Option<ColorFilter> {
+        let [r, g, b] = self.matrix()?;
+        let row_major = [
+            r[0], r[1], r[2], 0.0, 0.0,
+            g[0], g[1], g[2], 0.0, 0.0,
+            b[0], b[1], b[2], 0.0, 0.0,
+            0.0, 0.0, 0.0, 1.0, 0.0,
+        ];
+
+        color_filters::matrix_row_major(&row_major, None)
+            .with_working_color_space(ColorSpace::new_srgb_linear())
+    }
+}
+
+impl Overlay {
+    // COLOR VISION FUNCTIONS -----------------
+
+    /// Simulates or corrects for a color vision deficiency over everything drawn, starting with the next frame
+    pub fn set_color_vision_filter(&mut self, filter: ColorVisionFilter) {
+        self.color_vision = filter;
+    }
+
+    pub fn color_vision_filter(&self) -> ColorVisionFilter {
+        self.color_vision
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance(a: Color, b: Color) -> f32 {
+        let (a, b) = (a.to_f32(), b.to_f32());
+        (0..3).map(|index| (a[index] - b[index]).powi(2)).sum::<f32>().sqrt()
+    }
+
+    #[test]
+    fn test_simulation() {
+        let grey = Color::rgb(128, 128, 128);
+        for deficiency in [
+            ColorVisionDeficiency::Protanopia,
+            ColorVisionDeficiency::Deuteranopia,
+            ColorVisionDeficiency::Tritanopia,
+        ] {
+            // Neutral colors look the same to everyone
+            assert!(distance(ColorVisionFilter::Simulate(deficiency).apply(grey), grey) < 0.01);
+            assert!(distance(ColorVisionFilter::Daltonize(deficiency).apply(grey), grey) < 0.01);
+        }
+
+        // Red and green get much closer for a deuteranope
+        let (red, green) = (Color::rgb(220, 40, 40), Color::rgb(40, 180, 40));
+        let simulate = ColorVisionFilter::Simulate(ColorVisionDeficiency::Deuteranopia);
+        assert!(distance(simulate.apply(red), simulate.apply(green)) < distance(red, green) / 2.0);
+
+        // Daltonizing pulls them apart again in what the deuteranope sees
+        let daltonize = ColorVisionFilter::Daltonize(ColorVisionDeficiency::Deuteranopia);
+        let corrected = distance(simulate.apply(daltonize.apply(red)), simulate.apply(daltonize.apply(green)));
+        assert!(corrected > distance(simulate.apply(red), simulate.apply(green)));
+    }
+}
@@ -0,0 +1,54 @@
+use crate::core::{Color, Overlay, TextStyle};
+
+/// WCAG AA contrast for normal sized text
+pub const WCAG_AA: f32 = 4.5;
+/// WCAG AAA contrast for normal sized text
+pub const WCAG_AAA: f32 = 7.0;
+
+/// Plates more transparent than this mostly show the scene behind them, so they don't adjust text
+const MIN_PLATE_ALPHA: u8 = 128;
+/// What translucent plates are assumed to be drawn over, the real scene is unknown
+const ASSUMED_BACKDROP: Color = Color::BLACK;
+
+impl Overlay {
+    // CONTRAST FUNCTIONS ---------------------
+
+    /// Minimum contrast text keeps against the plates it draws on, like `TextStyle::with_background`
+    /// in lines and paragraphs, and `draw_label`. Text colors below it are lightened or darkened. `None` draws colors as given.
+    /// Defaults to `Some(WCAG_AA)`, so text on low contrast plates doesn't draw in exactly the color it was given.
+    pub fn set_min_text_contrast(&mut self, ratio: Option<f32>) {
+        self.min_text_contrast = ratio;
+    }
+
+    pub fn min_text_contrast(&self) -> Option<f32> {
+        self.min_text_contrast
+    }
+
+    /// The theme's `text` or `text_dark` color, whichever reads better on `background`
+    pub fn contrasting_text_color(&self, background: impl Into<Color>) -> Color {
+        let light = self.theme.color("text").unwrap_or(Color::WHITE);
+        let dark = self.theme.color("text_dark").unwrap_or(Color::BLACK);
+        background.into().pick_contrasting(light, dark)
+    }
+
+    /// Adjusts a style's text color to the minimum contrast against a plate, composited over a dark backdrop.
+    /// Gradient filled text and mostly transparent plates are left alone.
+    pub(crate) fn readable_on(&self, mut style: TextStyle, background: Color) -> TextStyle {
+        if background.a < MIN_PLATE_ALPHA {
+            return style;
+        }
+
+        if let Some(target) = self.min_text_contrast.filter(|_| style.brush.is_none()) {
+            style.color = style.color.with_min_contrast(background.over(ASSUMED_BACKDROP), target);
+        }
+        style
+    }
+
+    /// Adjusts a style's text color to its own background plate, if it has one
+    pub(crate) fn readable_on_own_plate(&self, style: TextStyle) -> TextStyle {
+        match style.background.as_ref().map(|background| background.brush.average_color()) {
+            Some(plate) => self.readable_on(style, plate),
+            None => style,
+        }
+    }
+}
@@ -0,0 +1,659 @@
+use std::collections::HashMap;
+use skia_safe::Path;
+use crate::core::{
+    Color, FitOptions, FontDescriptor, FontHandle, InterpolationSpace, Overlay, OverlayError, ParagraphStyle, PathTextLayout,
+    RichText, TextAnchor, TextStyle,
+    geometry::{Point, Size}
+};
+
+/// A single drawing operation, one variant per `Overlay::draw_*` function.
+/// Convenience functions like `draw_text` and `draw_outlined_text` record the `Text` they expand to.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DrawCommand {
+    Text { pos: Point, text: String, style: TextStyle },
+    FittedText { pos: Point, text: String, style: TextStyle, options: FitOptions },
+    Paragraph { pos: Point, text: String, style: TextStyle, layout: ParagraphStyle },
+    RichText { pos: Point, text: RichText, anchor: TextAnchor },
+    RichParagraph { pos: Point, text: RichText, layout: ParagraphStyle },
+    /// `path` is SVG path data, Skia paths can't be sent between threads
+    TextOnPath { path: String, text: String, style: TextStyle, layout: PathTextLayout },
+    /// `angles` are `(start, sweep)` in degrees
+    TextOnArc {
+        center: Point,
+        radius: f32,
+        angles: (f32, f32),
+        text: String,
+        style: TextStyle,
+        layout: PathTextLayout,
+    },
+    /// Theme keys are looked up when the command is executed, against the target's theme
+    ThemedText { pos: Point, text: String, key: String },
+    Component { pos: Point, size: Size, key: String },
+    Label { pos: Point, text: String, key: String },
+    Line { start: Point, end: Point, stroke_width: f32, color: Color },
+    GradientLine { start: Point, end: Point, stroke_width: f32, colors: [Color; 2], space: InterpolationSpace },
+    Rect { pos: Point, size: Size, stroke_width: f32, color: Color },
+    FilledRect { pos: Point, size: Size, color: Color },
+    GradientRect { pos: Point, size: Size, colors: [Color; 2], is_vertical: bool, space: InterpolationSpace },
+    RoundedRect { pos: Point, size: Size, radius: f32, stroke_width: f32, color: Color },
+    FilledRoundedRect { pos: Point, size: Size, radius: f32, color: Color },
+    GradientRoundedRect {
+        pos: Point,
+        size: Size,
+        radius: f32,
+        colors: [Color; 2],
+        is_vertical: bool,
+        space: InterpolationSpace,
+    },
+    Circle { center: Point, radius: f32, stroke_width: f32, color: Color },
+    FilledCircle { center: Point, radius: f32, color: Color },
+    GradientCircle { center: Point, radius: f32, colors: [Color; 2], is_radial: bool, space: InterpolationSpace },
+    Ellipse { center: Point, radii: Size, stroke_width: f32, color: Color },
+}
+
+impl DrawCommand {
+    /// Styles the command draws text with, whose fonts have to exist wherever it's executed
+    pub fn text_styles(&self) -> Vec<&TextStyle> {
+        match self {
+            DrawCommand::Text { style, .. }
+            | DrawCommand::FittedText { style, .. }
+            | DrawCommand::Paragraph { style, .. }
+            | DrawCommand::TextOnPath { style, .. }
+            | DrawCommand::TextOnArc { style, .. } => vec![style],
+            DrawCommand::RichText { text, .. } | DrawCommand::RichParagraph { text, .. } => {
+                text.spans.iter().map(|span| &span.style).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn text_styles_mut(&mut self) -> Vec<&mut TextStyle> {
+        match self {
+            DrawCommand::Text { style, .. }
+            | DrawCommand::FittedText { style, .. }
+            | DrawCommand::Paragraph { style, .. }
+            | DrawCommand::TextOnPath { style, .. }
+            | DrawCommand::TextOnArc { style, .. } => vec![style],
+            DrawCommand::RichText { text, .. } | DrawCommand::RichParagraph { text, .. } => {
+                text.spans.iter_mut().map(|span| &mut span.style).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Anything draw commands can be executed on. `Overlay` renders them, `DisplayList` records them.
+pub trait DrawTarget {
+    fn execute(&mut self, command: &DrawCommand) -> Result<(), OverlayError>;
+}
+
+/// Draw commands recorded ahead of time, through the same functions `Overlay` draws with.
+/// Needs no renderer, so frames can be built on another thread, inspected in tests or replayed later.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DisplayList {
+    commands: Vec<DrawCommand>,
+    /// Names for the fonts the commands use, filled in by `Overlay::describe_display_list_fonts`
+    fonts: Vec<(FontHandle, FontDescriptor)>,
+}
+
+impl DisplayList {
+    /// Written alongside serialized lists, bumped whenever a command's representation changes
+    pub const FORMAT_VERSION: u32 = 1;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, command: DrawCommand) {
+        self.commands.push(command);
+    }
+
+    pub fn commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.commands.clear();
+        self.fonts.clear();
+    }
+
+    /// Appends another list's commands, so a frame can be assembled from parts
+    pub fn append(&mut self, other: &DisplayList) {
+        self.commands.extend_from_slice(&other.commands);
+        for (handle, descriptor) in &other.fonts {
+            if !self.fonts.iter().any(|(described, _)| described == handle) {
+                self.fonts.push((*handle, descriptor.clone()));
+            }
+        }
+    }
+
+    /// Fonts described with `Overlay::describe_display_list_fonts`, or read along with a serialized list
+    pub fn fonts(&self) -> &[(FontHandle, FontDescriptor)] {
+        &self.fonts
+    }
+
+    /// Every font handle the commands draw with, without repeats
+    pub fn used_fonts(&self) -> Vec<FontHandle> {
+        let mut fonts = Vec::new();
+        for style in self.commands.iter().flat_map(DrawCommand::text_styles) {
+            if !fonts.contains(&style.font) {
+                fonts.push(style.font);
+            }
+        }
+        fonts
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_parts(commands: Vec<DrawCommand>, fonts: Vec<(FontHandle, FontDescriptor)>) -> Self {
+        Self { commands, fonts }
+    }
+
+    /// Executes every command in order, stopping at the first one that fails
+    pub fn replay(&self, target: &mut impl DrawTarget) -> Result<(), OverlayError> {
+        self.commands.iter().try_for_each(|command| target.execute(command))
+    }
+
+    // TEXT FUNCTIONS -------------------------
+
+    pub fn draw_text(&mut self, pos: impl Into<Point>, text: impl ToString, color: impl Into<Color>) {
+        self.draw_text_with(pos, text, TextStyle::new(FontHandle::DEFAULT, color));
+    }
+
+    pub fn draw_text_with(&mut self, pos: impl Into<Point>, text: impl ToString, style: impl Into<TextStyle>) {
+        self.push(DrawCommand::Text { pos: pos.into(), text: text.to_string(), style: style.into() });
+    }
+
+    pub fn draw_outlined_text(&mut self, pos: impl Into<Point>, text: &str, color: impl Into<Color>) {
+        let style = TextStyle::new(FontHandle::DEFAULT, color).with_outline(1.0, Color::BLACK);
+        self.draw_text_with(pos, text, style);
+    }
+
+    pub fn draw_outlined_text_with(&mut self, pos: impl Into<Point>, text: &str, style: impl Into<TextStyle>) {
+        let mut style = style.into();
+        if style.outline.is_none() {
+            style = style.with_outline(1.0, Color::BLACK);
+        }
+        self.draw_text_with(pos, text, style);
+    }
+
+    pub fn draw_fitted_text(
+        &mut self,
+        pos: impl Into<Point>,
+        text: impl AsRef<str>,
+        style: impl Into<TextStyle>,
+        options: &FitOptions
+    ) {
+        self.push(DrawCommand::FittedText {
+            pos: pos.into(),
+            text: text.as_ref().to_string(),
+            style: style.into(),
+            options: *options,
+        });
+    }
+
+    pub fn draw_paragraph(
+        &mut self,
+        pos: impl Into<Point>,
+        text: impl AsRef<str>,
+        style: impl Into<TextStyle>,
+        layout: &ParagraphStyle
+    ) {
+        self.push(DrawCommand::Paragraph {
+            pos: pos.into(),
+            text: text.as_ref().to_string(),
+            style: style.into(),
+            layout: layout.clone(),
+        });
+    }
+
+    pub fn draw_rich_text(&mut self, pos: impl Into<Point>, text: &RichText, anchor: TextAnchor) {
+        self.push(DrawCommand::RichText { pos: pos.into(), text: text.clone(), anchor });
+    }
+
+    pub fn draw_rich_paragraph(&mut self, pos: impl Into<Point>, text: &RichText, layout: &ParagraphStyle) {
+        self.push(DrawCommand::RichParagraph { pos: pos.into(), text: text.clone(), layout: layout.clone() });
+    }
+
+    pub fn draw_text_on_path(
+        &mut self,
+        path: &Path,
+        text: impl ToString,
+        style: impl Into<TextStyle>,
+        layout: &PathTextLayout
+    ) {
+        self.push(DrawCommand::TextOnPath {
+            path: path.to_svg(),
+            text: text.to_string(),
+            style: style.into(),
+            layout: *layout,
+        });
+    }
+
+    pub fn draw_text_on_arc(
+        &mut self,
+        center: impl Into<Point>,
+        radius: f32,
+        angles: (f32, f32),
+        text: impl ToString,
+        style: impl Into<TextStyle>,
+        layout: &PathTextLayout
+    ) {
+        self.push(DrawCommand::TextOnArc {
+            center: center.into(),
+            radius,
+            angles,
+            text: text.to_string(),
+            style: style.into(),
+            layout: *layout,
+        });
+    }
+
+    // THEME FUNCTIONS ------------------------
+
+    pub fn draw_themed_text(&mut self, pos: impl Into<Point>, text: impl ToString, key: &str) {
+        self.push(DrawCommand::ThemedText { pos: pos.into(), text: text.to_string(), key: key.to_string() });
+    }
+
+    pub fn draw_component(&mut self, pos: impl Into<Point>, size: impl Into<Size>, key: &str) {
+        self.push(DrawCommand::Component { pos: pos.into(), size: size.into(), key: key.to_string() });
+    }
+
+    pub fn draw_label(&mut self, pos: impl Into<Point>, text: impl ToString, key: &str) {
+        self.push(DrawCommand::Label { pos: pos.into(), text: text.to_string(), key: key.to_string() });
+    }
+
+    // LINE FUNCTIONS -------------------------
+
+    pub fn draw_line(
+        &mut self,
+        start: impl Into<Point>,
+        end: impl Into<Point>,
+        stroke_width: f32,
+        color: impl Into<Color>
+    ) {
+        self.push(DrawCommand::Line { start: start.into(), end: end.into(), stroke_width, color: color.into() });
+    }
+
+    /// Same as `draw_gradient_line_in`, interpolating in sRGB
+    pub fn draw_gradient_line(
+        &mut self,
+        start: impl Into<Point>,
+        end: impl Into<Point>,
+        stroke_width: f32,
+        color1: impl Into<Color>,
+        color2: impl Into<Color>
+    ) {
+        self.draw_gradient_line_in(start, end, stroke_width, color1, color2, InterpolationSpace::Srgb)
+    }
+
+    /// Blends the two colors in `space` instead of sRGB
+    pub fn draw_gradient_line_in(
+        &mut self,
+        start: impl Into<Point>,
+        end: impl Into<Point>,
+        stroke_width: f32,
+        color1: impl Into<Color>,
+        color2: impl Into<Color>,
+        space: InterpolationSpace,
+    ) {
+        self.push(DrawCommand::GradientLine {
+            start: start.into(),
+            end: end.into(),
+            stroke_width,
+            colors: [color1.into(), color2.into()],
+            space,
+        });
+    }
+
+    // RECTANGLE FUNCTIONS --------------------
+
+    pub fn draw_rect(
+        &mut self,
+        pos: impl Into<Point>,
+        size: impl Into<Size>,
+        stroke_width: f32,
+        color: impl Into<Color>
+    ) {
+        self.push(DrawCommand::Rect { pos: pos.into(), size: size.into(), stroke_width, color: color.into() });
+    }
+
+    pub fn draw_filled_rect(&mut self, pos: impl Into<Point>, size: impl Into<Size>, color: impl Into<Color>) {
+        self.push(DrawCommand::FilledRect { pos: pos.into(), size: size.into(), color: color.into() });
+    }
+
+    /// Same as `draw_gradient_rect_in`, interpolating in sRGB
+    pub fn draw_gradient_rect(
+        &mut self,
+        pos: impl Into<Point>,
+        size: impl Into<Size>,
+        color1: impl Into<Color>,
+        color2: impl Into<Color>,
+        is_vertical: bool
+    ) {
+        self.draw_gradient_rect_in(pos, size, color1, color2, is_vertical, InterpolationSpace::Srgb)
+    }
+
+    /// Blends the two colors in `space` instead of sRGB
+    pub fn draw_gradient_rect_in(
+        &mut self,
+        pos: impl Into<Point>,
+        size: impl Into<Size>,
+        color1: impl Into<Color>,
+        color2: impl Into<Color>,
+        is_vertical: bool,
+        space: InterpolationSpace,
+    ) {
+        self.push(DrawCommand::GradientRect {
+            pos: pos.into(),
+            size: size.into(),
+            colors: [color1.into(), color2.into()],
+            is_vertical,
+            space,
+        });
+    }
+
+    pub fn draw_rounded_rect(
+        &mut self,
+        pos: impl Into<Point>,
+        size: impl Into<Size>,
+        radius: f32,
+        stroke_width: f32,
+        color: impl Into<Color>
+    ) {
+        self.push(DrawCommand::RoundedRect {
+            pos: pos.into(),
+            size: size.into(),
+            radius,
+            stroke_width,
+            color: color.into(),
+        });
+    }
+
+    pub fn draw_filled_rounded_rect(
+        &mut self,
+        pos: impl Into<Point>,
+        size: impl Into<Size>,
+        radius: f32,
+        color: impl Into<Color>
+    ) {
+        self.push(DrawCommand::FilledRoundedRect {
+            pos: pos.into(),
+            size: size.into(),
+            radius,
+            color: color.into(),
+        });
+    }
+
+    /// Same as `draw_gradient_rounded_rect_in`, interpolating in sRGB
+    pub fn draw_gradient_rounded_rect(
+        &mut self,
+        pos: impl Into<Point>,
+        size: impl Into<Size>,
+        radius: f32,
+        color1: impl Into<Color>,
+        color2: impl Into<Color>,
+        is_vertical: bool
+    ) {
+        self.draw_gradient_rounded_rect_in(pos, size, radius, color1, color2, is_vertical, InterpolationSpace::Srgb)
+    }
+
+    /// Blends the two colors in `space` instead of sRGB
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_gradient_rounded_rect_in(
+        &mut self,
+        pos: impl Into<Point>,
+        size: impl Into<Size>,
+        radius: f32,
+        color1: impl Into<Color>,
+        color2: impl Into<Color>,
+        is_vertical: bool,
+        space: InterpolationSpace,
+    ) {
+        self.push(DrawCommand::GradientRoundedRect {
+            pos: pos.into(),
+            size: size.into(),
+            radius,
+            colors: [color1.into(), color2.into()],
+            is_vertical,
+            space,
+        });
+    }
+
+    // CIRCLE FUNCTIONS -----------------------
+
+    pub fn draw_circle(
+        &mut self,
+        center: impl Into<Point>,
+        radius: f32,
+        stroke_width: f32,
+        color: impl Into<Color>
+    ) {
+        self.push(DrawCommand::Circle { center: center.into(), radius, stroke_width, color: color.into() });
+    }
+
+    pub fn draw_filled_circle(&mut self, center: impl Into<Point>, radius: f32, color: impl Into<Color>) {
+        self.push(DrawCommand::FilledCircle { center: center.into(), radius, color: color.into() });
+    }
+
+    /// Same as `draw_gradient_circle_in`, interpolating in sRGB
+    pub fn draw_gradient_circle(
+        &mut self,
+        center: impl Into<Point>,
+        radius: f32,
+        color1: impl Into<Color>,
+        color2: impl Into<Color>,
+        is_radial: bool
+    ) {
+        self.draw_gradient_circle_in(center, radius, color1, color2, is_radial, InterpolationSpace::Srgb)
+    }
+
+    /// Blends the two colors in `space` instead of sRGB
+    pub fn draw_gradient_circle_in(
+        &mut self,
+        center: impl Into<Point>,
+        radius: f32,
+        color1: impl Into<Color>,
+        color2: impl Into<Color>,
+        is_radial: bool,
+        space: InterpolationSpace,
+    ) {
+        self.push(DrawCommand::GradientCircle {
+            center: center.into(),
+            radius,
+            colors: [color1.into(), color2.into()],
+            is_radial,
+            space,
+        });
+    }
+
+    // ELLIPSE FUNCTIONS ---------------------
+
+    pub fn draw_ellipse(
+        &mut self,
+        center: impl Into<Point>,
+        radii: impl Into<Size>,
+        stroke_width: f32,
+        color: impl Into<Color>
+    ) {
+        self.push(DrawCommand::Ellipse {
+            center: center.into(),
+            radii: radii.into(),
+            stroke_width,
+            color: color.into(),
+        });
+    }
+}
+
+impl DrawTarget for DisplayList {
+    fn execute(&mut self, command: &DrawCommand) -> Result<(), OverlayError> {
+        self.push(command.clone());
+        Ok(())
+    }
+}
+
+impl FromIterator<DrawCommand> for DisplayList {
+    fn from_iter<I: IntoIterator<Item = DrawCommand>>(iter: I) -> Self {
+        Self { commands: iter.into_iter().collect(), fonts: Vec::new() }
+    }
+}
+
+impl<'a> IntoIterator for &'a DisplayList {
+    type Item = &'a DrawCommand;
+    type IntoIter = std::slice::Iter<'a, DrawCommand>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.commands.iter()
+    }
+}
+
+impl DrawTarget for Overlay {
+    fn execute(&mut self, command: &DrawCommand) -> Result<(), OverlayError> {
+        match command {
+            DrawCommand::Text { pos, text, style } => self.draw_text_with(*pos, text, style).map(drop),
+            DrawCommand::FittedText { pos, text, style, options } => {
+                self.draw_fitted_text(*pos, text, style, options).map(drop)
+            }
+            DrawCommand::Paragraph { pos, text, style, layout } => {
+                self.draw_paragraph(*pos, text, style, layout).map(drop)
+            }
+            DrawCommand::RichText { pos, text, anchor } => {
+                self.draw_rich_text(*pos, text, *anchor).map(drop)
+            }
+            DrawCommand::RichParagraph { pos, text, layout } => {
+                self.draw_rich_paragraph(*pos, text, layout).map(drop)
+            }
+            DrawCommand::TextOnPath { path, text, style, layout } => {
+                let path = Path::from_svg(path).ok_or(OverlayError::DrawFailed)?;
+                self.draw_text_on_path(&path, text, style, layout).map(drop)
+            }
+            DrawCommand::TextOnArc { center, radius, angles, text, style, layout } => {
+                self.draw_text_on_arc(*center, *radius, *angles, text, style, layout).map(drop)
+            }
+            DrawCommand::ThemedText { pos, text, key } => self.draw_themed_text(*pos, text, key).map(drop),
+            DrawCommand::Component { pos, size, key } => self.draw_component(*pos, *size, key).map(drop),
+            DrawCommand::Label { pos, text, key } => self.draw_label(*pos, text, key).map(drop),
+            DrawCommand::Line { start, end, stroke_width, color } => {
+                self.draw_line(*start, *end, *stroke_width, *color)
+            }
+            DrawCommand::GradientLine { start, end, stroke_width, colors: [color1, color2], space } => {
+                self.draw_gradient_line_in(*start, *end, *stroke_width, *color1, *color2, *space)
+            }
+            DrawCommand::Rect { pos, size, stroke_width, color } => {
+                self.draw_rect(*pos, *size, *stroke_width, *color)
+            }
+            DrawCommand::FilledRect { pos, size, color } => self.draw_filled_rect(*pos, *size, *color),
+            DrawCommand::GradientRect { pos, size, colors: [color1, color2], is_vertical, space } => {
+                self.draw_gradient_rect_in(*pos, *size, *color1, *color2, *is_vertical, *space)
+            }
+            DrawCommand::RoundedRect { pos, size, radius, stroke_width, color } => {
+                self.draw_rounded_rect(*pos, *size, *radius, *stroke_width, *color)
+            }
+            DrawCommand::FilledRoundedRect { pos, size, radius, color } => {
+                self.draw_filled_rounded_rect(*pos, *size, *radius, *color)
+            }
+            DrawCommand::GradientRoundedRect {
+                pos, size, radius, colors: [color1, color2], is_vertical, space
+            } => {
+                self.draw_gradient_rounded_rect_in(*pos, *size, *radius, *color1, *color2, *is_vertical, *space)
+            }
+            DrawCommand::Circle { center, radius, stroke_width, color } => {
+                self.draw_circle(*center, *radius, *stroke_width, *color)
+            }
+            DrawCommand::FilledCircle { center, radius, color } => {
+                self.draw_filled_circle(*center, *radius, *color)
+            }
+            DrawCommand::GradientCircle { center, radius, colors: [color1, color2], is_radial, space } => {
+                self.draw_gradient_circle_in(*center, *radius, *color1, *color2, *is_radial, *space)
+            }
+            DrawCommand::Ellipse { center, radii, stroke_width, color } => {
+                self.draw_ellipse(*center, *radii, *stroke_width, *color)
+            }
+        }
+    }
+}
+
+impl Overlay {
+    // DISPLAY LIST FUNCTIONS -----------------
+
+    /// Draws a recorded list, stopping at the first command that fails.
+    /// Fonts the list describes are looked up by name, so lists saved by another overlay draw with this one's fonts.
+    pub fn draw_display_list(&mut self, list: &DisplayList) -> Result<(), OverlayError> {
+        if list.fonts.is_empty() {
+            return list.replay(self);
+        }
+
+        let mut local_fonts = HashMap::new();
+        for (handle, descriptor) in &list.fonts {
+            let local = self.fonts.resolve(descriptor)
+                .ok_or_else(|| OverlayError::FontNotFound(descriptor.name.clone().unwrap_or_default()))?;
+            local_fonts.insert(*handle, local);
+        }
+
+        for command in &list.commands {
+            let mut command = command.clone();
+            for style in command.text_styles_mut() {
+                if let Some(local) = local_fonts.get(&style.font) {
+                    style.font = *local;
+                }
+            }
+            self.execute(&command)?;
+        }
+
+        Ok(())
+    }
+
+    /// Records a name for every font a display list uses, so it can be serialized and drawn by another overlay.
+    /// Fonts need a name from `set_font_name`, or to be a variant of a named font or the default font.
+    pub fn describe_display_list_fonts(&self, list: &mut DisplayList) -> Result<(), OverlayError> {
+        let mut fonts = Vec::new();
+        for handle in list.used_fonts() {
+            let descriptor = self.fonts.describe(handle).ok_or(OverlayError::UnnamedFont(handle))?;
+            fonts.push((handle, descriptor));
+        }
+
+        list.fonts = fonts;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording() {
+        let mut list = DisplayList::new();
+        list.draw_filled_rect((10.0, 20.0), (100.0, 50.0), Color::RED);
+        list.draw_outlined_text((15.0, 40.0), "HP", Color::WHITE);
+        list.draw_label((0.0, 0.0), "Paused", "label");
+
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.commands()[0], DrawCommand::FilledRect {
+            pos: Point::new(10.0, 20.0),
+            size: Size::new(100.0, 50.0),
+            color: Color::RED,
+        });
+        assert!(matches!(
+            &list.commands()[1],
+            DrawCommand::Text { text, style, .. } if text == "HP" && style.outline.is_some()
+        ));
+
+        // Replaying into another list copies it command for command
+        let mut copy = DisplayList::new();
+        list.replay(&mut copy).unwrap();
+        assert_eq!(copy, list);
+
+        // Lists are built on one thread and drawn on another
+        fn assert_send<T: Send>() {}
+        assert_send::<DisplayList>();
+    }
+}
@@ -1,7 +1,10 @@
-use skia_safe::{Paint, Point, Rect, TextBlob, TileMode, Shader, Color4f};
+use skia_safe::{BlurStyle, Canvas, MaskFilter, Paint, Point, Rect, TextBlob};
 use crate::core::{
-    Overlay, OverlayError,
-    helper::to_color_4f
+    Brush, Color, FontHandle, InterpolationSpace, Overlay, OverlayError, TextStyle,
+    geometry,
+    brush::{linear_gradient_shader, radial_gradient_shader},
+    helper::to_color_4f,
+    text::decoration_rects
 };
 
 // TODO: CREATE FILLED AND GRADIENT ELLIPSE DRAW FUNCS
@@ -11,57 +14,104 @@ impl Overlay {
 
     pub fn draw_text(
         &mut self,
-        (x, y): (f32, f32),
+        pos: impl Into<geometry::Point>,
         text: impl ToString,
-        color: (u8, u8, u8, u8)
-    ) -> Result <(), OverlayError> {
+        color: impl Into<Color>
+    ) -> Result<geometry::Rect, OverlayError> {
+        self.draw_text_with(pos, text, TextStyle::new(FontHandle::DEFAULT, color))
+    }
+
+    /// Draws a line of text positioned by the style's anchor, along with its background box and decorations.
+    /// Returns the advance by ascent+descent box the text occupies, without the background padding.
+    pub fn draw_text_with(
+        &mut self,
+        pos: impl Into<geometry::Point>,
+        text: impl ToString,
+        style: impl Into<TextStyle>
+    ) -> Result<geometry::Rect, OverlayError> {
+        let text = text.to_string();
+        let style = style.into();
+
+        // Text on its own plate keeps readable contrast against it
+        let style = self.readable_on_own_plate(style);
+
+        // Shaped once and reused from the cache while the string and font stay the same
+        let shaped = self.shaped_text(&style, &text);
+        let metrics = shaped.metrics;
+
+        let pos: geometry::Point = pos.into();
+        let (x, y) = style.anchor.baseline_origin(pos.into(), &metrics);
+        let ink_bounds = metrics.bounds.with_offset((x, y));
+        let blobs: Vec<(TextBlob, Point)> = shaped.blobs.into_iter()
+            .map(|(text_blob, origin)| (text_blob, origin + Point::new(x, y)))
+            .collect();
+        let decorations: Vec<Rect> = decoration_rects(self.fonts.get(style.font), &style.decoration, metrics.width)
+            .into_iter()
+            .map(|rect| rect.with_offset((x, y)))
+            .collect();
+
         let canvas = self.skia_context.as_mut()
             .expect("Skia context should be initialized")
             .canvas();
 
-        let text = text.to_string();
+        if let (Some(background), Some(rect)) = (&style.background, style.background_rect(&metrics, (x, y))) {
+            let paint = background.brush.to_paint(rect);
+            canvas.draw_round_rect(rect, background.corner_radius, background.corner_radius, &paint);
+        }
 
-        // Convert the RGBA color tuple to Skia Color
-        let color = to_color_4f(color);
+        draw_text_blobs(canvas, &blobs, &style, ink_bounds);
 
-        // Create a Paint object with the desired stroke width and color
-        let mut paint = Paint::new(color, None);
-        paint.set_anti_alias(true);
-
-        let text_blob = TextBlob::new(text, &self.font).expect("Failed to create TextBlob");
+        // Decorations share the glyph fill unless they have their own color
+        let paint = match style.decoration.color {
+            Some(color) => Brush::Solid(color).to_paint(ink_bounds),
+            None => style.fill_paint(ink_bounds),
+        };
 
-        canvas.draw_text_blob(&text_blob, Point::new(x,y), &paint);
+        for rect in decorations {
+            canvas.draw_rect(rect, &paint);
+        }
 
-        Ok(())
+        Ok(metrics.layout_bounds((x, y)).into())
     }
 
-
+    /// Draws text with a 1px black outline
     pub fn draw_outlined_text(
         &mut self,
-        (x, y): (f32, f32),
+        pos: impl Into<geometry::Point>,
         text: &str,
-        color: (u8, u8, u8, u8)
-    ) -> Result<(), OverlayError> {
-        // Outline
-        self.draw_text((x - 1.0, y), text, (0, 0, 0, 255))?;
-        self.draw_text((x + 1.0, y), text, (0, 0, 0, 255))?;
-        self.draw_text((x - 1.0, y), text, (0, 0, 0, 255))?;
-        self.draw_text((x + 1.0, y), text, (0, 0, 0, 255))?;
-        // Main text
-        self.draw_text((x, y), text, color)?;
+        color: impl Into<Color>
+    ) -> Result<geometry::Rect, OverlayError> {
+        let style = TextStyle::new(FontHandle::DEFAULT, color).with_outline(1.0, Color::BLACK);
+        self.draw_text_with(pos, text, style)
+    }
 
-        Ok(())
+    /// Draws text with a 1px black outline unless the style already has one
+    pub fn draw_outlined_text_with(
+        &mut self,
+        pos: impl Into<geometry::Point>,
+        text: &str,
+        style: impl Into<TextStyle>
+    ) -> Result<geometry::Rect, OverlayError> {
+        let mut style = style.into();
+        if style.outline.is_none() {
+            style = style.with_outline(1.0, Color::BLACK);
+        }
+
+        self.draw_text_with(pos, text, style)
     }
 
     // LINE FUNCTIONS -------------------------
 
     pub fn draw_line(
         &mut self,
-        start: (f32, f32),
-        end: (f32, f32),
+        start: impl Into<geometry::Point>,
+        end: impl Into<geometry::Point>,
         stroke_width: f32,
-        color: (u8, u8, u8, u8)
+        color: impl Into<Color>
     ) -> Result<(), OverlayError> {
+        let start: geometry::Point = start.into();
+        let end: geometry::Point = end.into();
+
         let canvas = self.skia_context.as_mut()
             .expect("Skia context should be initialized")
             .canvas();
@@ -74,33 +124,47 @@ impl Overlay {
         paint.set_style(skia_safe::paint::Style::Stroke);
 
         canvas.draw_line(
-            Point::new(start.0, start.1),
-            Point::new(end.0, end.1),
+            Point::new(start.x, start.y),
+            Point::new(end.x, end.y),
             &paint
         );
         Ok(())
     }
 
+    /// Same as `draw_gradient_line_in`, interpolating in sRGB
     pub fn draw_gradient_line(
         &mut self,
-        start: (f32, f32),
-        end: (f32, f32),
+        start: impl Into<geometry::Point>,
+        end: impl Into<geometry::Point>,
+        stroke_width: f32,
+        color1: impl Into<Color>,
+        color2: impl Into<Color>
+    ) -> Result<(), OverlayError> {
+        self.draw_gradient_line_in(start, end, stroke_width, color1, color2, InterpolationSpace::Srgb)
+    }
+
+    /// Blends the two colors in `space` instead of sRGB
+    pub fn draw_gradient_line_in(
+        &mut self,
+        start: impl Into<geometry::Point>,
+        end: impl Into<geometry::Point>,
         stroke_width: f32,
-        color1: (u8, u8, u8, u8),
-        color2: (u8, u8, u8, u8),
+        color1: impl Into<Color>,
+        color2: impl Into<Color>,
+        space: InterpolationSpace,
     ) -> Result<(), OverlayError> {
+        let start: geometry::Point = start.into();
+        let end: geometry::Point = end.into();
+
         let canvas = self.skia_context.as_mut()
             .expect("Skia context should be initialized")
             .canvas();
 
-        let color1 = to_color_4f(color1);
-        let color2 = to_color_4f(color2);
-
-        let colors: &[Color4f] = &[color1, color2];
+        let colors = [color1.into(), color2.into()];
 
         let points = [
-            Point::new(start.0, start.1),
-            Point::new(end.0, end.1)
+            Point::new(start.x, start.y),
+            Point::new(end.x, end.y)
         ];
 
         let mut paint = Paint::default();
@@ -108,14 +172,7 @@ impl Overlay {
         paint.set_anti_alias(true);
         paint.set_style(skia_safe::paint::Style::Stroke);
 
-        let shader = Shader::linear_gradient(
-            (Point::new(start.0, start.1), Point::new(end.0, end.1)),
-            colors,
-            None,
-            TileMode::Clamp,
-            None,
-            None,
-        );
+        let shader = linear_gradient_shader((points[0], points[1]), &colors, space);
 
         paint.set_shader(shader);
 
@@ -128,16 +185,19 @@ impl Overlay {
 
     pub fn draw_rect(
         &mut self,
-        (x, y): (f32, f32),
-        (width, height): (f32, f32),
+        pos: impl Into<geometry::Point>,
+        size: impl Into<geometry::Size>,
         stroke_width: f32,
-        color: (u8, u8, u8, u8)
+        color: impl Into<Color>
     ) -> Result<(), OverlayError> {
+        let geometry::Point { x, y } = pos.into();
+        let geometry::Size { width, height } = size.into();
+
         let canvas = self.skia_context.as_mut()
             .expect("Skia context should be initialized")
             .canvas();
 
-        // Convert the RGBA color tuple to Skia Color
+        // Convert the color to a Skia Color4f
         let color = to_color_4f(color);
 
         // Create a Paint object with the desired stroke width and color
@@ -155,10 +215,13 @@ impl Overlay {
 
     pub fn draw_filled_rect(
         &mut self,
-        (x, y): (f32, f32),
-        (width, height): (f32, f32),
-        color: (u8, u8, u8, u8)
+        pos: impl Into<geometry::Point>,
+        size: impl Into<geometry::Size>,
+        color: impl Into<Color>
     ) -> Result<(), OverlayError> {
+        let geometry::Point { x, y } = pos.into();
+        let geometry::Size { width, height } = size.into();
+
         let canvas = self.skia_context.as_mut()
             .expect("Skia context should be initialized")
             .canvas();
@@ -175,22 +238,36 @@ impl Overlay {
         Ok(())
     }
 
+    /// Same as `draw_gradient_rect_in`, interpolating in sRGB
     pub fn draw_gradient_rect(
         &mut self,
-        (x, y): (f32, f32),
-        (width, height): (f32, f32),
-        color1: (u8, u8, u8, u8),
-        color2: (u8, u8, u8, u8),
+        pos: impl Into<geometry::Point>,
+        size: impl Into<geometry::Size>,
+        color1: impl Into<Color>,
+        color2: impl Into<Color>,
+        is_vertical: bool
+    ) -> Result<(), OverlayError> {
+        self.draw_gradient_rect_in(pos, size, color1, color2, is_vertical, InterpolationSpace::Srgb)
+    }
+
+    /// Blends the two colors in `space` instead of sRGB
+    pub fn draw_gradient_rect_in(
+        &mut self,
+        pos: impl Into<geometry::Point>,
+        size: impl Into<geometry::Size>,
+        color1: impl Into<Color>,
+        color2: impl Into<Color>,
         is_vertical: bool,
+        space: InterpolationSpace,
     ) -> Result<(), OverlayError> {
+        let geometry::Point { x, y } = pos.into();
+        let geometry::Size { width, height } = size.into();
+
         let canvas = self.skia_context.as_mut()
             .expect("Skia context should be initialized")
             .canvas();
 
-        let color1 = to_color_4f(color1);
-        let color2 = to_color_4f(color2);
-
-        let colors: &[Color4f] = &[color1, color2];
+        let colors = [color1.into(), color2.into()];
 
         let mut paint = Paint::default();
         paint.set_anti_alias(true);
@@ -202,14 +279,7 @@ impl Overlay {
             (Point::new(x, y), Point::new(x + width, y))
         };
 
-        let shader = Shader::linear_gradient(
-            (start, end),
-            colors,
-            None,
-            TileMode::Clamp,
-            None,
-            None,
-        );
+        let shader = linear_gradient_shader((start, end), &colors, space);
 
         paint.set_shader(shader);
         canvas.draw_rect(Rect::new(x, y, x + width, y + height), &paint);
@@ -222,12 +292,15 @@ impl Overlay {
 
     pub fn draw_rounded_rect(
         &mut self,
-        (x, y): (f32, f32),
-        (width, height): (f32, f32),
+        pos: impl Into<geometry::Point>,
+        size: impl Into<geometry::Size>,
         radius: f32,
         stroke_width: f32,
-        color: (u8, u8, u8, u8)
+        color: impl Into<Color>
     ) -> Result<(), OverlayError> {
+        let geometry::Point { x, y } = pos.into();
+        let geometry::Size { width, height } = size.into();
+
         let canvas = self.skia_context.as_mut()
             .expect("Skia context should be initialized")
             .canvas();
@@ -247,11 +320,14 @@ impl Overlay {
 
     pub fn draw_filled_rounded_rect(
         &mut self,
-        (x, y): (f32, f32),
-        (width, height): (f32, f32),
+        pos: impl Into<geometry::Point>,
+        size: impl Into<geometry::Size>,
         radius: f32,
-        color: (u8, u8, u8, u8)
+        color: impl Into<Color>
     ) -> Result<(), OverlayError> {
+        let geometry::Point { x, y } = pos.into();
+        let geometry::Size { width, height } = size.into();
+
         let canvas = self.skia_context.as_mut()
             .expect("Skia context should be initialized")
             .canvas();
@@ -268,23 +344,39 @@ impl Overlay {
         Ok(())
     }
 
+    /// Same as `draw_gradient_rounded_rect_in`, interpolating in sRGB
     pub fn draw_gradient_rounded_rect(
         &mut self,
-        (x, y): (f32, f32),
-        (width, height): (f32, f32),
+        pos: impl Into<geometry::Point>,
+        size: impl Into<geometry::Size>,
         radius: f32,
-        color1: (u8, u8, u8, u8),
-        color2: (u8, u8, u8, u8),
+        color1: impl Into<Color>,
+        color2: impl Into<Color>,
+        is_vertical: bool
+    ) -> Result<(), OverlayError> {
+        self.draw_gradient_rounded_rect_in(pos, size, radius, color1, color2, is_vertical, InterpolationSpace::Srgb)
+    }
+
+    /// Blends the two colors in `space` instead of sRGB
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_gradient_rounded_rect_in(
+        &mut self,
+        pos: impl Into<geometry::Point>,
+        size: impl Into<geometry::Size>,
+        radius: f32,
+        color1: impl Into<Color>,
+        color2: impl Into<Color>,
         is_vertical: bool,
+        space: InterpolationSpace,
     ) -> Result<(), OverlayError> {
+        let geometry::Point { x, y } = pos.into();
+        let geometry::Size { width, height } = size.into();
+
         let canvas = self.skia_context.as_mut()
             .expect("Skia context should be initialized")
             .canvas();
 
-        let color1 = to_color_4f(color1);
-        let color2 = to_color_4f(color2);
-
-        let colors: &[Color4f] = &[color1, color2];
+        let colors = [color1.into(), color2.into()];
 
         let mut paint = Paint::default();
         paint.set_anti_alias(true);
@@ -296,14 +388,7 @@ impl Overlay {
             (Point::new(x, y), Point::new(x + width, y))
         };
 
-        let shader = Shader::linear_gradient(
-            (start, end),
-            colors,
-            None,
-            TileMode::Clamp,
-            None,
-            None,
-        );
+        let shader = linear_gradient_shader((start, end), &colors, space);
 
         paint.set_shader(shader);
         let rect = Rect::new(x, y, x + width, y + height);
@@ -316,11 +401,13 @@ impl Overlay {
 
     pub fn draw_circle(
         &mut self,
-        center: (f32, f32),
+        center: impl Into<geometry::Point>,
         radius: f32,
         stroke_width: f32,
-        color: (u8, u8, u8, u8)
+        color: impl Into<Color>
     ) -> Result<(), OverlayError> {
+        let center: geometry::Point = center.into();
+
         let canvas = self.skia_context.as_mut()
             .expect("Skia context should be initialized")
             .canvas();
@@ -332,17 +419,19 @@ impl Overlay {
         paint.set_anti_alias(true);
         paint.set_style(skia_safe::paint::Style::Stroke);
 
-        canvas.draw_circle(Point::new(center.0, center.1), radius, &paint);
+        canvas.draw_circle(Point::new(center.x, center.y), radius, &paint);
 
         Ok(())
     }
 
     pub fn draw_filled_circle(
         &mut self,
-        center: (f32, f32),
+        center: impl Into<geometry::Point>,
         radius: f32,
-        color: (u8, u8, u8, u8)
+        color: impl Into<Color>
     ) -> Result<(), OverlayError> {
+        let center: geometry::Point = center.into();
+
         let canvas = self.skia_context.as_mut()
             .expect("Skia context should be initialized")
             .canvas();
@@ -353,58 +442,60 @@ impl Overlay {
         paint.set_anti_alias(true);
         paint.set_style(skia_safe::paint::Style::Fill);
 
-        canvas.draw_circle(Point::new(center.0, center.1), radius, &paint);
+        canvas.draw_circle(Point::new(center.x, center.y), radius, &paint);
 
         Ok(())
     }
 
+    /// Same as `draw_gradient_circle_in`, interpolating in sRGB
     pub fn draw_gradient_circle(
         &mut self,
-        center: (f32, f32),
+        center: impl Into<geometry::Point>,
         radius: f32,
-        color1: (u8, u8, u8, u8),
-        color2: (u8, u8, u8, u8),
+        color1: impl Into<Color>,
+        color2: impl Into<Color>,
+        is_radial: bool
+    ) -> Result<(), OverlayError> {
+        self.draw_gradient_circle_in(center, radius, color1, color2, is_radial, InterpolationSpace::Srgb)
+    }
+
+    /// Blends the two colors in `space` instead of sRGB
+    pub fn draw_gradient_circle_in(
+        &mut self,
+        center: impl Into<geometry::Point>,
+        radius: f32,
+        color1: impl Into<Color>,
+        color2: impl Into<Color>,
         is_radial: bool,
+        space: InterpolationSpace,
     ) -> Result<(), OverlayError> {
+        let center: geometry::Point = center.into();
+
         let canvas = self.skia_context.as_mut()
             .expect("Skia context should be initialized")
             .canvas();
 
-        let color1 = to_color_4f(color1);
-        let color2 = to_color_4f(color2);
-
-        let colors: &[Color4f] = &[color1, color2];
+        let colors = [color1.into(), color2.into()];
 
         let mut paint = Paint::default();
         paint.set_anti_alias(true);
         paint.set_style(skia_safe::paint::Style::Fill);
 
         let shader = if is_radial {
-            Shader::radial_gradient(
-                Point::new(center.0, center.1),
-                radius,
-                colors,
-                None,
-                TileMode::Clamp,
-                None,
-                None,
-            )
+            radial_gradient_shader(Point::new(center.x, center.y), radius, &colors, space)
         } else {
-            Shader::linear_gradient(
+            linear_gradient_shader(
                 (
-                    Point::new(center.0 - radius, center.1),
-                    Point::new(center.0 + radius, center.1)
+                    Point::new(center.x - radius, center.y),
+                    Point::new(center.x + radius, center.y)
                 ),
-                colors,
-                None,
-                TileMode::Clamp,
-                None,
-                None,
+                &colors,
+                space
             )
         };
 
         paint.set_shader(shader);
-        canvas.draw_circle(Point::new(center.0, center.1), radius, &paint);
+        canvas.draw_circle(Point::new(center.x, center.y), radius, &paint);
 
         Ok(())
     }
@@ -413,11 +504,14 @@ impl Overlay {
 
     pub fn draw_ellipse(
         &mut self,
-        center: (f32, f32),
-        (radius_x, radius_y): (f32, f32),
+        center: impl Into<geometry::Point>,
+        radii: impl Into<geometry::Size>,
         stroke_width: f32,
-        color: (u8, u8, u8, u8)
+        color: impl Into<Color>
     ) -> Result<(), OverlayError> {
+        let center: geometry::Point = center.into();
+        let geometry::Size { width: radius_x, height: radius_y } = radii.into();
+
         let canvas = self.skia_context.as_mut()
             .expect("Skia context should be initialized")
             .canvas();
@@ -430,13 +524,48 @@ impl Overlay {
         paint.set_style(skia_safe::paint::Style::Stroke);
 
         let rect = Rect::new(
-            center.0 - radius_x,
-            center.1 - radius_y,
-            center.0 + radius_x,
-            center.1 + radius_y
+            center.x - radius_x,
+            center.y - radius_y,
+            center.x + radius_x,
+            center.y + radius_y
         );
         canvas.draw_oval(rect, &paint);
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Paints positioned text blobs with the style's shadow, outline and fill, in that order.
+/// Gradient brushes span `ink_bounds`.
+pub(crate) fn draw_text_blobs(canvas: &Canvas, blobs: &[(TextBlob, Point)], style: &TextStyle, ink_bounds: Rect) {
+    // Shadow first, covering the outline too so it reads as one shape
+    if let Some(shadow) = style.shadow {
+        let mut paint = Paint::new(to_color_4f(shadow.color), None);
+        paint.set_anti_alias(true);
+
+        if let Some(outline) = style.outline {
+            paint.set_style(skia_safe::paint::Style::StrokeAndFill);
+            paint.set_stroke_width(outline.width * 2.0);
+            paint.set_stroke_join(skia_safe::paint::Join::Round);
+        }
+        if shadow.blur > 0.0 {
+            paint.set_mask_filter(MaskFilter::blur(BlurStyle::Normal, shadow.blur, false));
+        }
+
+        for (text_blob, origin) in blobs {
+            canvas.draw_text_blob(text_blob, *origin + Point::new(shadow.offset.0, shadow.offset.1), &paint);
+        }
+    }
+
+    if let Some(paint) = style.outline_paint() {
+        for (text_blob, origin) in blobs {
+            canvas.draw_text_blob(text_blob, *origin, &paint);
+        }
+    }
+
+    let paint = style.fill_paint(ink_bounds);
+
+    for (text_blob, origin) in blobs {
+        canvas.draw_text_blob(text_blob, *origin, &paint);
+    }
+}
@@ -0,0 +1,674 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
+use skia_safe::{Font, FontMgr, FontStyle, Typeface, TypefaceId, Unichar};
+use skia_safe::font_style::{Slant, Weight, Width};
+use skia_safe::textlayout::{FontCollection, TypefaceFontProvider};
+use skia_safe::Shaper;
+use crate::core::{Overlay, OverlayError, TextRendering};
+
+/// Lightweight reference to a font registered with an `Overlay`.
+/// Handles are cheap to copy and only valid for the overlay that created them.
+/// Serialized display lists carry a `FontDescriptor` for each handle so they can be drawn elsewhere.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontHandle(pub(crate) usize);
+
+/// A font by the name it was given with `Overlay::set_font_name`, so another overlay or run can find it again.
+/// `name` is `None` for the font the overlay was constructed with.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontDescriptor {
+    pub name: Option<String>,
+    pub bold: bool,
+    pub italic: bool,
+    pub size: f32,
+}
+
+impl FontHandle {
+    /// The font the overlay was constructed with
+    pub const DEFAULT: FontHandle = FontHandle(0);
+}
+
+/// Bold/italic/size derivative of a registered font, cached so repeated lookups don't grow the registry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct FontVariant {
+    base: FontHandle,
+    bold: bool,
+    italic: bool,
+    size_bits: u32,
+}
+
+/// A slice of text that is drawn with a single font after fallback resolution
+#[derive(Debug, Clone)]
+pub(crate) struct FontRun {
+    pub font: Font,
+    pub range: Range<usize>,
+}
+
+/// Owns every font an overlay can draw with. Index 0 is always the constructor font.
+pub(crate) struct FontRegistry {
+    font_mgr: FontMgr,
+    fonts: Vec<Font>,
+    names: HashMap<String, FontHandle>,
+    variants: HashMap<FontVariant, FontHandle>,
+
+    // Paragraph layout, every registered font is also exposed here under a unique alias
+    provider: TypefaceFontProvider,
+    collection: FontCollection,
+
+    // Complex script shaping, falls back to system fonts through the font manager
+    shaper: Shaper,
+
+    // Fallback
+    fallbacks: Vec<FontHandle>,
+    system_fallback: bool,
+    /// Font that draws a character missing from a typeface, `None` when the typeface itself is used
+    fallback_cache: Mutex<HashMap<(TypefaceId, char), Option<Typeface>>>,
+
+    // Rendering, one entry per font
+    default_rendering: TextRendering,
+    renderings: Vec<TextRendering>,
+    synthetic_bold: HashSet<FontHandle>,
+    /// Font each one was derived from, so rendering changes reach variants of variants and resized copies
+    parents: Vec<Option<FontHandle>>,
+}
+
+impl FontRegistry {
+    /// Creates a registry whose default font is an installed family
+    pub fn from_family(family: &str, size: f32) -> Result<Self, OverlayError> {
+        let mut registry = Self::empty();
+        let typeface = registry.family_typeface(family, FontStyle::normal());
+        registry.insert(make_font(typeface, size)?);
+        Ok(registry)
+    }
+
+    /// Creates a registry whose default font is parsed from raw TTF/OTF/TTC data
+    pub fn from_bytes(bytes: &[u8], collection_index: usize, size: f32) -> Result<Self, OverlayError> {
+        let mut registry = Self::empty();
+        let typeface = registry.bytes_typeface(bytes, collection_index);
+        registry.insert(make_font(typeface, size)?);
+        Ok(registry)
+    }
+
+    fn empty() -> Self {
+        let font_mgr = FontMgr::default();
+        let provider = TypefaceFontProvider::new();
+
+        // Registered fonts are looked up first, the system manager covers fallback
+        let mut collection = FontCollection::new();
+        collection.set_asset_font_manager(Some(provider.clone().into()));
+        collection.set_default_font_manager(font_mgr.clone(), None);
+
+        let shaper = Shaper::new(font_mgr.clone());
+
+        Self {
+            font_mgr,
+            fonts: Vec::new(),
+            names: HashMap::new(),
+            variants: HashMap::new(),
+
+            // Paragraph layout
+            provider,
+            collection,
+
+            // Complex script shaping
+            shaper,
+
+            // Fallback
+            fallbacks: Vec::new(),
+            system_fallback: true,
+            fallback_cache: Mutex::new(HashMap::new()),
+
+            // Rendering
+            default_rendering: TextRendering::default(),
+            renderings: Vec::new(),
+            synthetic_bold: HashSet::new(),
+            parents: Vec::new(),
+        }
+    }
+
+    /// Adds a newly created font with the default rendering settings and returns its handle
+    pub fn insert(&mut self, font: Font) -> FontHandle {
+        self.insert_with_rendering(font, self.default_rendering, false, None)
+    }
+
+    /// Adds a copy of a registered font that keeps the base font's rendering settings
+    fn insert_derived(&mut self, base: FontHandle, font: Font, synthetic_bold: bool) -> FontHandle {
+        let synthetic_bold = synthetic_bold || self.synthetic_bold.contains(&base);
+        self.insert_with_rendering(font, self.rendering(base), synthetic_bold, Some(base))
+    }
+
+    fn insert_with_rendering(
+        &mut self,
+        mut font: Font,
+        rendering: TextRendering,
+        synthetic_bold: bool,
+        parent: Option<FontHandle>
+    ) -> FontHandle {
+        let handle = FontHandle(self.fonts.len());
+
+        self.provider.register_typeface(font.typeface(), family_alias(handle).as_str());
+        self.collection.clear_caches();
+
+        rendering.apply(&mut font, synthetic_bold);
+        if synthetic_bold {
+            self.synthetic_bold.insert(handle);
+        }
+
+        self.fonts.push(font);
+        self.renderings.push(rendering);
+        self.parents.push(parent);
+        handle
+    }
+
+    pub fn rendering(&self, handle: FontHandle) -> TextRendering {
+        self.renderings.get(handle.0).copied().unwrap_or(self.renderings[0])
+    }
+
+    /// Changes the rendering settings of a font and everything derived from it, returning every handle that changed
+    pub fn set_rendering(&mut self, handle: FontHandle, rendering: TextRendering) -> Vec<FontHandle> {
+        let affected: Vec<FontHandle> = (0..self.fonts.len())
+            .map(FontHandle)
+            .filter(|candidate| self.derives_from(*candidate, handle))
+            .collect();
+
+        for handle in &affected {
+            self.apply_rendering(*handle, rendering);
+        }
+
+        affected
+    }
+
+    /// Changes the rendering settings of every font, including ones registered later
+    pub fn set_default_rendering(&mut self, rendering: TextRendering) {
+        self.default_rendering = rendering;
+
+        for index in 0..self.fonts.len() {
+            self.apply_rendering(FontHandle(index), rendering);
+        }
+    }
+
+    /// True for the font itself and any font derived from it, directly or through other variants
+    fn derives_from(&self, mut candidate: FontHandle, ancestor: FontHandle) -> bool {
+        loop {
+            if candidate == ancestor {
+                return true;
+            }
+
+            match self.parents.get(candidate.0).copied().flatten() {
+                Some(parent) => candidate = parent,
+                None => return false,
+            }
+        }
+    }
+
+    fn apply_rendering(&mut self, handle: FontHandle, rendering: TextRendering) {
+        let synthetic_bold = self.synthetic_bold.contains(&handle);
+        rendering.apply(&mut self.fonts[handle.0], synthetic_bold);
+        self.renderings[handle.0] = rendering;
+    }
+
+    /// Looks up a font, falling back to the default font for handles from another overlay
+    pub fn get(&self, handle: FontHandle) -> &Font {
+        self.fonts.get(handle.0).unwrap_or(&self.fonts[0])
+    }
+
+    pub fn contains(&self, handle: FontHandle) -> bool {
+        handle.0 < self.fonts.len()
+    }
+
+    /// Looks up a font by the name it was given with `Overlay::set_font_name`
+    pub fn by_name(&self, name: &str) -> Option<FontHandle> {
+        self.names.get(name).copied()
+    }
+
+    /// Describes a font by name, `None` for fonts that aren't named or derived from a named font
+    pub fn describe(&self, handle: FontHandle) -> Option<FontDescriptor> {
+        if !self.contains(handle) {
+            return None;
+        }
+
+        let size = self.get(handle).size();
+        let name = self.names.iter()
+            .filter(|(_, named)| **named == handle)
+            .map(|(name, _)| name)
+            .min();
+        if let Some(name) = name {
+            return Some(FontDescriptor { name: Some(name.clone()), bold: false, italic: false, size });
+        }
+        if handle == FontHandle::DEFAULT {
+            return Some(FontDescriptor { name: None, bold: false, italic: false, size });
+        }
+
+        if let Some((key, _)) = self.variants.iter().find(|(_, variant)| **variant == handle) {
+            let base = self.describe(key.base)?;
+            return Some(FontDescriptor { bold: base.bold || key.bold, italic: base.italic || key.italic, size, ..base });
+        }
+
+        // Resized copies like the ones `Overlay::load_font_size` makes
+        let parent = self.parents.get(handle.0).copied().flatten()?;
+        Some(FontDescriptor { size, ..self.describe(parent)? })
+    }
+
+    /// Finds or derives the font a descriptor refers to, `None` if its name isn't registered
+    pub fn resolve(&mut self, descriptor: &FontDescriptor) -> Option<FontHandle> {
+        let base = match &descriptor.name {
+            Some(name) => self.by_name(name)?,
+            None => FontHandle::DEFAULT,
+        };
+
+        if !descriptor.bold && !descriptor.italic && self.get(base).size() == descriptor.size {
+            return Some(base);
+        }
+        Some(self.variant(base, descriptor.bold, descriptor.italic, Some(descriptor.size)))
+    }
+
+    /// Returns a bold/italic/resized copy of a font, registering it the first time it's asked for.
+    /// Uses the real bold or italic face of the family when installed, otherwise synthesizes it.
+    pub fn variant(&mut self, base: FontHandle, bold: bool, italic: bool, size: Option<f32>) -> FontHandle {
+        let base_font = self.get(base).clone();
+        let size = size.filter(|size| size.is_finite() && *size > 0.0).unwrap_or(base_font.size());
+
+        let key = FontVariant { base, bold, italic, size_bits: size.to_bits() };
+        if let Some(handle) = self.variants.get(&key) {
+            return *handle;
+        }
+
+        let base_style = base_font.typeface().font_style();
+        let wanted_style = FontStyle::new(
+            if bold { Weight::BOLD } else { base_style.weight() },
+            Width::NORMAL,
+            if italic { Slant::Italic } else { base_style.slant() }
+        );
+
+        let mut font = base_font.with_size(size).unwrap_or(base_font);
+        let mut synthetic_bold = false;
+        if wanted_style != base_style {
+            let typeface = self.font_mgr
+                .match_family_style(font.typeface().family_name(), wanted_style)
+                .unwrap_or_else(|| font.typeface());
+            let style = typeface.font_style();
+            font.set_typeface(typeface);
+
+            synthetic_bold = bold && *style.weight() < *Weight::SEMI_BOLD;
+            if italic && style.slant() == Slant::Upright {
+                font.set_skew_x(-0.25);
+            }
+        }
+
+        let handle = self.insert_derived(base, font, synthetic_bold);
+        self.variants.insert(key, handle);
+        handle
+    }
+
+    /// Finds an installed family, falling back to the default typeface if it's missing
+    pub fn family_typeface(&self, family: &str, style: FontStyle) -> Typeface {
+        let typeface = self.font_mgr.match_family_style(family, style);
+        self.typeface_or_default(typeface, style, family)
+    }
+
+    /// Parses font data, falling back to the default typeface if it isn't a valid font
+    pub fn bytes_typeface(&self, bytes: &[u8], collection_index: usize) -> Typeface {
+        let typeface = self.font_mgr.new_from_data(bytes, collection_index);
+        self.typeface_or_default(typeface, FontStyle::normal(), "<font data>")
+    }
+
+    /// Splits text into runs so every character is drawn by a font that actually has a glyph for it.
+    /// The primary font is tried first, then the user fallback chain, then the system font manager.
+    /// `primary` is usually a registered font, possibly with variable axes applied.
+    pub fn font_runs(&self, primary: &Font, text: &str) -> Vec<FontRun> {
+        let mut runs: Vec<FontRun> = Vec::new();
+
+        for (index, ch) in text.char_indices() {
+            let range = index..index + ch.len_utf8();
+
+            // Joiners, selectors and modifiers must stay in the run of the character they modify
+            let font = match runs.last() {
+                Some(run) if is_cluster_continuation(ch) => run.font.clone(),
+                _ => self.font_for_char(primary, ch),
+            };
+
+            match runs.last_mut() {
+                Some(run) if same_font(&run.font, &font) => run.range.end = range.end,
+                _ => runs.push(FontRun { font, range }),
+            }
+        }
+
+        runs
+    }
+
+    pub fn shaper(&self) -> &Shaper {
+        &self.shaper
+    }
+
+    /// Font manager HarfBuzz shaping may fall back to, none when system fallback is disabled
+    pub fn system_font_mgr(&self) -> Option<FontMgr> {
+        self.system_fallback.then(|| self.font_mgr.clone())
+    }
+
+    /// Font collection for paragraph layout, shares the fonts registered here
+    pub fn font_collection(&self) -> FontCollection {
+        self.collection.clone()
+    }
+
+    /// Family names that select a font, followed by its fallback chain, in a font collection
+    pub fn paragraph_families(&self, handle: FontHandle) -> Vec<String> {
+        let handle = if self.contains(handle) { handle } else { FontHandle::DEFAULT };
+
+        std::iter::once(handle)
+            .chain(self.fallbacks.iter().copied())
+            .map(family_alias)
+            .collect()
+    }
+
+    fn font_for_char(&self, primary: &Font, ch: char) -> Font {
+        if ch.is_control() || ch.is_whitespace() || has_glyph(primary, ch) {
+            return primary.clone();
+        }
+
+        // Asking the system is slow, so each character is only resolved once per typeface
+        let key = (primary.typeface().unique_id(), ch);
+        let mut cache = self.fallback_cache.lock().unwrap_or_else(PoisonError::into_inner);
+        let typeface = cache.entry(key)
+            .or_insert_with(|| self.fallback_typeface(primary, ch))
+            .clone();
+
+        match typeface {
+            Some(typeface) => {
+                let mut font = primary.clone();
+                font.set_typeface(typeface);
+                font
+            }
+            None => primary.clone(),
+        }
+    }
+
+    /// Typeface that has a glyph for a character the primary font is missing, resized to match by the caller
+    fn fallback_typeface(&self, primary: &Font, ch: char) -> Option<Typeface> {
+        // User configured chain first
+        for handle in &self.fallbacks {
+            let fallback = self.get(*handle);
+            if has_glyph(fallback, ch) {
+                return Some(fallback.typeface());
+            }
+        }
+
+        if self.system_fallback {
+            return self.font_mgr.match_family_style_character(
+                "",
+                primary.typeface().font_style(),
+                &[],
+                ch as Unichar
+            );
+        }
+
+        // Nothing has it, let the primary font draw its missing glyph box
+        None
+    }
+
+    /// Forgets resolved fallbacks after the fallback settings change
+    fn clear_fallback_cache(&mut self) {
+        self.fallback_cache.get_mut().unwrap_or_else(PoisonError::into_inner).clear();
+    }
+
+    fn typeface_or_default(&self, typeface: Option<Typeface>, style: FontStyle, name: &str) -> Typeface {
+        typeface.unwrap_or_else(|| {
+            log::warn!("failed to load font '{}', falling back to the default typeface", name);
+
+            // The system default, or Skia's empty typeface when no fonts are installed at all
+            self.font_mgr.legacy_make_typeface(None, style)
+                .unwrap_or_else(|| Font::default().typeface())
+        })
+    }
+}
+
+/// Unique family name a registered font is known by inside the font collection
+fn family_alias(handle: FontHandle) -> String {
+    format!("overlay-font-{}", handle.0)
+}
+
+fn has_glyph(font: &Font, ch: char) -> bool {
+    font.unichar_to_glyph(ch as Unichar) != 0
+}
+
+fn same_font(a: &Font, b: &Font) -> bool {
+    Typeface::equal(a.typeface(), b.typeface())
+}
+
+/// Characters that never start a new cluster: combining marks, ZWJ, variation selectors and skin tones
+pub(crate) fn is_cluster_continuation(ch: char) -> bool {
+    matches!(
+        ch as u32,
+        0x0300..=0x036F | 0x200C | 0x200D | 0xFE00..=0xFE0F | 0x1F3FB..=0x1F3FF | 0xE0020..=0xE007F
+    )
+}
+
+/// Builds a font, rejecting sizes Skia can't render
+fn make_font(typeface: Typeface, size: f32) -> Result<Font, OverlayError> {
+    if !size.is_finite() || size <= 0.0 {
+        return Err(OverlayError::InvalidFontSize);
+    }
+
+    Ok(Font::new(typeface, size))
+}
+
+/// Reads a font file from disk
+pub(crate) fn read_font_file(path: &Path) -> Result<Vec<u8>, OverlayError> {
+    std::fs::read(path).map_err(|e| {
+        log::warn!("failed to read font file {}: {:?}", path.display(), e);
+        OverlayError::FailedToReadFontFile(path.display().to_string())
+    })
+}
+
+impl Overlay {
+    // FONT FUNCTIONS -------------------------
+
+    /// Registers an installed font family with the given weight/width/slant and size.
+    /// Missing families fall back to the default typeface with a warning.
+    pub fn load_font(
+        &mut self,
+        family: impl AsRef<str>,
+        style: FontStyle,
+        size: f32
+    ) -> Result<FontHandle, OverlayError> {
+        let typeface = self.fonts.family_typeface(family.as_ref(), style);
+        Ok(self.fonts.insert(make_font(typeface, size)?))
+    }
+
+    /// Registers a font from raw TTF/OTF/TTC data, e.g. from `include_bytes!`.
+    /// `collection_index` picks the face inside a TTC collection and is 0 for single fonts.
+    pub fn load_font_bytes(
+        &mut self,
+        bytes: &[u8],
+        collection_index: usize,
+        size: f32
+    ) -> Result<FontHandle, OverlayError> {
+        let typeface = self.fonts.bytes_typeface(bytes, collection_index);
+        Ok(self.fonts.insert(make_font(typeface, size)?))
+    }
+
+    /// Registers a TTF/OTF/TTC font file from disk
+    pub fn load_font_file(
+        &mut self,
+        path: impl AsRef<Path>,
+        collection_index: usize,
+        size: f32
+    ) -> Result<FontHandle, OverlayError> {
+        let bytes = read_font_file(path.as_ref())?;
+        self.load_font_bytes(&bytes, collection_index, size)
+    }
+
+    /// Registers a copy of an existing font at a different size
+    pub fn load_font_size(&mut self, handle: FontHandle, size: f32) -> Result<FontHandle, OverlayError> {
+        if !self.fonts.contains(handle) {
+            return Err(OverlayError::InvalidFontHandle);
+        }
+
+        // `with_size` keeps every other setting of the source font
+        let font = self.fonts.get(handle).with_size(size)
+            .filter(|_| size > 0.0)
+            .ok_or(OverlayError::InvalidFontSize)?;
+
+        Ok(self.fonts.insert_derived(handle, font, false))
+    }
+
+    /// Sets fonts that are tried, in order, for characters the requested font can't draw.
+    /// The system font manager is still consulted afterwards unless disabled.
+    pub fn set_fallback_fonts(&mut self, fallbacks: &[FontHandle]) -> Result<(), OverlayError> {
+        if !fallbacks.iter().all(|handle| self.fonts.contains(*handle)) {
+            return Err(OverlayError::InvalidFontHandle);
+        }
+
+        self.fonts.fallbacks = fallbacks.to_vec();
+        self.fonts.clear_fallback_cache();
+
+        // Cached strings may have resolved to different fonts
+        self.clear_text_cache();
+        Ok(())
+    }
+
+    /// Enables or disables asking the system for a font that covers missing characters
+    pub fn set_system_font_fallback(&mut self, enabled: bool) {
+        self.fonts.system_fallback = enabled;
+        self.fonts.clear_fallback_cache();
+
+        if enabled {
+            self.fonts.collection.enable_font_fallback();
+        } else {
+            self.fonts.collection.disable_font_fallback();
+        }
+
+        self.clear_text_cache();
+    }
+
+    /// Gives a font a name so markup like `<font=mono>` and themes can refer to it
+    pub fn set_font_name(&mut self, name: impl Into<String>, handle: FontHandle) -> Result<(), OverlayError> {
+        if !self.fonts.contains(handle) {
+            return Err(OverlayError::InvalidFontHandle);
+        }
+
+        self.fonts.names.insert(name.into(), handle);
+        Ok(())
+    }
+
+    /// Looks up a font registered with `set_font_name`
+    pub fn font_by_name(&self, name: impl AsRef<str>) -> Option<FontHandle> {
+        self.fonts.by_name(name.as_ref())
+    }
+
+    /// Handle to the font passed to `Overlay::new`
+    pub fn default_font(&self) -> FontHandle {
+        FontHandle::DEFAULT
+    }
+
+    /// Direct access to a registered Skia font
+    pub fn font(&self, handle: FontHandle) -> &Font {
+        self.fonts.get(handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> FontRegistry {
+        let mut registry = FontRegistry::empty();
+        registry.insert(Font::default().with_size(16.0).expect("size is valid"));
+        registry
+    }
+
+    #[test]
+    fn test_handles() {
+        let mut registry = registry();
+        let large = registry.insert(Font::default().with_size(32.0).expect("size is valid"));
+
+        assert_eq!(large, FontHandle(1));
+        assert!(registry.contains(large));
+        assert_eq!(registry.get(large).size(), 32.0);
+
+        // Handles from another overlay draw with the default font
+        assert!(!registry.contains(FontHandle(7)));
+        assert_eq!(registry.get(FontHandle(7)).size(), 16.0);
+    }
+
+    #[test]
+    fn test_size_variants_are_cached() {
+        let mut registry = registry();
+
+        let resized = registry.variant(FontHandle::DEFAULT, false, false, Some(24.0));
+        assert_ne!(resized, FontHandle::DEFAULT);
+        assert_eq!(registry.get(resized).size(), 24.0);
+        assert_eq!(registry.variant(FontHandle::DEFAULT, false, false, Some(24.0)), resized);
+
+        // Unusable sizes keep the base size
+        let same_size = registry.variant(FontHandle::DEFAULT, false, false, Some(-1.0));
+        assert_eq!(registry.get(same_size).size(), 16.0);
+        assert_eq!(registry.variant(FontHandle::DEFAULT, false, false, None), same_size);
+    }
+
+    #[test]
+    fn test_cluster_continuation() {
+        assert!(is_cluster_continuation('\u{301}'));
+        assert!(is_cluster_continuation('\u{200d}'));
+        assert!(is_cluster_continuation('\u{fe0f}'));
+        assert!(is_cluster_continuation('\u{1f3fd}'));
+        assert!(!is_cluster_continuation('e'));
+        assert!(!is_cluster_continuation('\u{1f44d}'));
+    }
+
+    #[test]
+    fn test_font_runs_without_fallback() {
+        let mut registry = registry();
+        registry.system_fallback = false;
+
+        // Nothing can draw the characters, so the primary font keeps the whole line and its missing glyph boxes
+        let text = "e\u{301} \u{5d0}\u{1f44d}\u{1f3fd}";
+        let runs = registry.font_runs(registry.get(FontHandle::DEFAULT), text);
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].range, 0..text.len());
+        assert!(registry.font_runs(registry.get(FontHandle::DEFAULT), "").is_empty());
+    }
+
+    #[test]
+    fn test_rendering_reaches_derived_fonts() {
+        let mut fonts = registry();
+        let other = fonts.insert(Font::default());
+        let large = fonts.variant(FontHandle::DEFAULT, false, false, Some(24.0));
+        let larger = fonts.variant(large, false, false, Some(32.0));
+
+        let affected = fonts.set_rendering(FontHandle::DEFAULT, TextRendering::SMALL_TEXT);
+        assert_eq!(affected, vec![FontHandle::DEFAULT, large, larger]);
+        assert_eq!(fonts.rendering(larger), TextRendering::SMALL_TEXT);
+        assert_eq!(fonts.get(larger).hinting(), skia_safe::FontHinting::Slight);
+        assert_eq!(fonts.rendering(other), TextRendering::default());
+
+        // Fonts registered later pick up the default settings
+        fonts.set_default_rendering(TextRendering::ANIMATED);
+        let added = fonts.insert(Font::default());
+        assert_eq!(fonts.rendering(added), TextRendering::ANIMATED);
+        assert_eq!(fonts.rendering(other), TextRendering::ANIMATED);
+    }
+
+    #[test]
+    fn test_describe_and_resolve() {
+        let mut registry = registry();
+        let hud = registry.insert(Font::default().with_size(20.0).expect("size is valid"));
+        registry.names.insert("hud".to_string(), hud);
+        let unnamed = registry.insert(Font::default());
+        let large_hud = registry.variant(hud, false, false, Some(30.0));
+
+        let descriptor = |name: Option<&str>, size| FontDescriptor { name: name.map(String::from), bold: false, italic: false, size };
+        assert_eq!(registry.describe(FontHandle::DEFAULT), Some(descriptor(None, 16.0)));
+        assert_eq!(registry.describe(hud), Some(descriptor(Some("hud"), 20.0)));
+        assert_eq!(registry.describe(large_hud), Some(descriptor(Some("hud"), 30.0)));
+        assert_eq!(registry.describe(unnamed), None);
+        assert_eq!(registry.describe(FontHandle(99)), None);
+
+        assert_eq!(registry.resolve(&descriptor(Some("hud"), 20.0)), Some(hud));
+        assert_eq!(registry.resolve(&descriptor(Some("hud"), 30.0)), Some(large_hud));
+        assert_eq!(registry.resolve(&descriptor(None, 16.0)), Some(FontHandle::DEFAULT));
+        assert_eq!(registry.resolve(&descriptor(Some("missing"), 16.0)), None);
+    }
+}
@@ -0,0 +1,139 @@
+use skia_safe::{Font, FontHinting};
+use skia_safe::font::Edging;
+use crate::core::{FontHandle, Overlay, OverlayError};
+
+/// How glyph edges are smoothed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextEdging {
+    /// Hard pixel edges, crisp for tiny pixel fonts
+    Aliased,
+    #[default]
+    AntiAlias,
+    /// Per color channel smoothing for LCD screens, only helps over opaque backgrounds
+    SubpixelAntiAlias,
+}
+
+impl From<TextEdging> for Edging {
+    fn from(edging: TextEdging) -> Self {
+        match edging {
+            TextEdging::Aliased => Edging::Alias,
+            TextEdging::AntiAlias => Edging::AntiAlias,
+            TextEdging::SubpixelAntiAlias => Edging::SubpixelAntiAlias,
+        }
+    }
+}
+
+/// How strongly glyph outlines are snapped to the pixel grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextHinting {
+    None,
+    Slight,
+    #[default]
+    Normal,
+    /// Sharpest at small sizes, distorts glyph shapes the most
+    Full,
+}
+
+impl From<TextHinting> for FontHinting {
+    fn from(hinting: TextHinting) -> Self {
+        match hinting {
+            TextHinting::None => FontHinting::None,
+            TextHinting::Slight => FontHinting::Slight,
+            TextHinting::Normal => FontHinting::Normal,
+            TextHinting::Full => FontHinting::Full,
+        }
+    }
+}
+
+/// Rasterization settings for a font. The default matches a freshly created Skia font.
+/// Measurement uses the same fonts, so widths always agree with what is drawn.
+/// Paragraphs apply edging, embolden and hinting off from the first span, but always use subpixel positioning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TextRendering {
+    pub edging: TextEdging,
+    pub hinting: TextHinting,
+    /// Places glyphs at fractional pixel positions instead of rounding each one
+    pub subpixel: bool,
+    /// Thickens outlines, a synthetic bold on top of the typeface's own weight
+    pub embolden: bool,
+    /// Uses unhinted advances so text width scales smoothly with the font size
+    pub linear_metrics: bool,
+}
+
+impl TextRendering {
+    /// Crisp small text: anti-aliased, slight hinting and subpixel positioning
+    pub const SMALL_TEXT: TextRendering = TextRendering {
+        edging: TextEdging::AntiAlias,
+        hinting: TextHinting::Slight,
+        subpixel: true,
+        embolden: false,
+        linear_metrics: false,
+    };
+
+    /// Smooth scaling for animated text: no hinting, subpixel positioning and linear metrics
+    pub const ANIMATED: TextRendering = TextRendering {
+        edging: TextEdging::AntiAlias,
+        hinting: TextHinting::None,
+        subpixel: true,
+        embolden: false,
+        linear_metrics: true,
+    };
+
+    /// Applies the settings to a font. `synthetic_bold` keeps the embolden a bold variant needed
+    /// because its family had no real bold face.
+    pub(crate) fn apply(&self, font: &mut Font, synthetic_bold: bool) {
+        font.set_edging(self.edging.into());
+        font.set_hinting(self.hinting.into());
+        font.set_subpixel(self.subpixel);
+        font.set_embolden(self.embolden || synthetic_bold);
+        font.set_linear_metrics(self.linear_metrics);
+    }
+}
+
+impl Overlay {
+    // FONT RENDERING FUNCTIONS ---------------
+
+    /// Applies rendering settings to every registered font and to fonts loaded afterwards
+    pub fn set_text_rendering(&mut self, rendering: TextRendering) {
+        self.fonts.set_default_rendering(rendering);
+
+        // Widths and ink bounds depend on hinting and edging
+        self.clear_text_cache();
+    }
+
+    /// Applies rendering settings to one font and every bold/italic/size variant derived from it, however indirectly
+    pub fn set_font_rendering(&mut self, handle: FontHandle, rendering: TextRendering) -> Result<(), OverlayError> {
+        if !self.fonts.contains(handle) {
+            return Err(OverlayError::InvalidFontHandle);
+        }
+
+        for affected in self.fonts.set_rendering(handle, rendering) {
+            self.invalidate_text_cache(affected);
+        }
+
+        Ok(())
+    }
+
+    /// Current rendering settings of a font
+    pub fn font_rendering(&self, handle: FontHandle) -> TextRendering {
+        self.fonts.rendering(handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let mut font = Font::default();
+        TextRendering::ANIMATED.apply(&mut font, false);
+        assert_eq!(font.hinting(), FontHinting::None);
+        assert!(font.is_subpixel() && font.is_linear_metrics() && !font.is_embolden());
+
+        // A synthetic bold keeps its embolden whatever the settings say
+        TextRendering { edging: TextEdging::Aliased, ..Default::default() }.apply(&mut font, true);
+        assert_eq!(font.edging(), Edging::Alias);
+        assert!(font.is_embolden() && !font.is_subpixel());
+    }
+}
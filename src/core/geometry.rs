@@ -0,0 +1,581 @@
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+/// A position in pixels, origin at the top left of the overlay
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// A width and height in pixels
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Size {
+    pub width: f32,
+    pub height: f32,
+}
+
+/// An axis aligned rectangle given by its top left corner and size
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Space on each side of a rectangle, e.g. padding around a label
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Insets {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+impl Point {
+    pub const ZERO: Point = Point::new(0.0, 0.0);
+
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn distance(self, other: Point) -> f32 {
+        (other - self).length()
+    }
+
+    /// Distance from the origin
+    pub fn length(self) -> f32 {
+        self.x.hypot(self.y)
+    }
+
+    pub fn lerp(self, other: Point, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Size {
+    pub const ZERO: Size = Size::new(0.0, 0.0);
+
+    pub const fn new(width: f32, height: f32) -> Self {
+        Self { width, height }
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.width <= 0.0 || self.height <= 0.0
+    }
+
+    pub fn area(self) -> f32 {
+        self.width * self.height
+    }
+}
+
+impl Rect {
+    pub const fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    pub fn from_origin_size(origin: impl Into<Point>, size: impl Into<Size>) -> Self {
+        let (origin, size) = (origin.into(), size.into());
+        Self::new(origin.x, origin.y, size.width, size.height)
+    }
+
+    pub fn from_ltrb(left: f32, top: f32, right: f32, bottom: f32) -> Self {
+        Self::new(left, top, right - left, bottom - top)
+    }
+
+    /// A rectangle of `size` centered on `center`
+    pub fn from_center(center: impl Into<Point>, size: impl Into<Size>) -> Self {
+        let (center, size) = (center.into(), size.into());
+        Self::new(center.x - size.width / 2.0, center.y - size.height / 2.0, size.width, size.height)
+    }
+
+    pub fn left(self) -> f32 {
+        self.x
+    }
+
+    pub fn top(self) -> f32 {
+        self.y
+    }
+
+    pub fn right(self) -> f32 {
+        self.x + self.width
+    }
+
+    pub fn bottom(self) -> f32 {
+        self.y + self.height
+    }
+
+    pub fn origin(self) -> Point {
+        Point::new(self.x, self.y)
+    }
+
+    pub fn size(self) -> Size {
+        Size::new(self.width, self.height)
+    }
+
+    pub fn center(self) -> Point {
+        Point::new(self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.size().is_empty()
+    }
+
+    /// Whether a point lies inside, the right and bottom edges are exclusive
+    pub fn contains(self, point: impl Into<Point>) -> bool {
+        let point = point.into();
+        point.x >= self.left() && point.x < self.right() && point.y >= self.top() && point.y < self.bottom()
+    }
+
+    pub fn intersects(self, other: Rect) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// The overlapping area, `None` when the rectangles don't overlap
+    pub fn intersection(self, other: Rect) -> Option<Rect> {
+        let left = self.left().max(other.left());
+        let top = self.top().max(other.top());
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+
+        (left < right && top < bottom).then(|| Rect::from_ltrb(left, top, right, bottom))
+    }
+
+    /// The smallest rectangle containing both. Empty rectangles are ignored.
+    pub fn union(self, other: Rect) -> Rect {
+        if other.is_empty() {
+            return self;
+        }
+        if self.is_empty() {
+            return other;
+        }
+
+        Rect::from_ltrb(
+            self.left().min(other.left()),
+            self.top().min(other.top()),
+            self.right().max(other.right()),
+            self.bottom().max(other.bottom())
+        )
+    }
+
+    /// Shrinks every side by the matching inset
+    pub fn inset(self, insets: impl Into<Insets>) -> Rect {
+        let insets = insets.into();
+        Rect::from_ltrb(
+            self.left() + insets.left,
+            self.top() + insets.top,
+            self.right() - insets.right,
+            self.bottom() - insets.bottom
+        )
+    }
+
+    /// Grows every side by the matching inset
+    pub fn outset(self, insets: impl Into<Insets>) -> Rect {
+        self.inset(-insets.into())
+    }
+
+    pub fn offset(self, by: impl Into<Point>) -> Rect {
+        let by = by.into();
+        Rect::new(self.x + by.x, self.y + by.y, self.width, self.height)
+    }
+}
+
+impl Insets {
+    pub const ZERO: Insets = Insets::uniform(0.0);
+
+    pub const fn new(top: f32, right: f32, bottom: f32, left: f32) -> Self {
+        Self { top, right, bottom, left }
+    }
+
+    pub const fn uniform(inset: f32) -> Self {
+        Self::new(inset, inset, inset, inset)
+    }
+
+    pub const fn symmetric(horizontal: f32, vertical: f32) -> Self {
+        Self::new(vertical, horizontal, vertical, horizontal)
+    }
+
+    /// Left plus right
+    pub fn horizontal(self) -> f32 {
+        self.left + self.right
+    }
+
+    /// Top plus bottom
+    pub fn vertical(self) -> f32 {
+        self.top + self.bottom
+    }
+}
+
+// OPERATORS ------------------------------
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, other: Point) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, other: Point) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<f32> for Point {
+    type Output = Point;
+
+    fn mul(self, scale: f32) -> Point {
+        Point::new(self.x * scale, self.y * scale)
+    }
+}
+
+impl Div<f32> for Point {
+    type Output = Point;
+
+    fn div(self, scale: f32) -> Point {
+        Point::new(self.x / scale, self.y / scale)
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+/// Moves a point by a size, e.g. from a corner to the opposite one
+impl Add<Size> for Point {
+    type Output = Point;
+
+    fn add(self, size: Size) -> Point {
+        Point::new(self.x + size.width, self.y + size.height)
+    }
+}
+
+impl Add for Size {
+    type Output = Size;
+
+    fn add(self, other: Size) -> Size {
+        Size::new(self.width + other.width, self.height + other.height)
+    }
+}
+
+impl Sub for Size {
+    type Output = Size;
+
+    fn sub(self, other: Size) -> Size {
+        Size::new(self.width - other.width, self.height - other.height)
+    }
+}
+
+impl Mul<f32> for Size {
+    type Output = Size;
+
+    fn mul(self, scale: f32) -> Size {
+        Size::new(self.width * scale, self.height * scale)
+    }
+}
+
+impl Div<f32> for Size {
+    type Output = Size;
+
+    fn div(self, scale: f32) -> Size {
+        Size::new(self.width / scale, self.height / scale)
+    }
+}
+
+impl Add<Point> for Rect {
+    type Output = Rect;
+
+    fn add(self, by: Point) -> Rect {
+        self.offset(by)
+    }
+}
+
+impl Sub<Point> for Rect {
+    type Output = Rect;
+
+    fn sub(self, by: Point) -> Rect {
+        self.offset(-by)
+    }
+}
+
+impl Add for Insets {
+    type Output = Insets;
+
+    fn add(self, other: Insets) -> Insets {
+        Insets::new(self.top + other.top, self.right + other.right, self.bottom + other.bottom, self.left + other.left)
+    }
+}
+
+impl Neg for Insets {
+    type Output = Insets;
+
+    fn neg(self) -> Insets {
+        Insets::new(-self.top, -self.right, -self.bottom, -self.left)
+    }
+}
+
+// CONVERSIONS ----------------------------
+
+impl From<(f32, f32)> for Point {
+    fn from((x, y): (f32, f32)) -> Self {
+        Point::new(x, y)
+    }
+}
+
+impl From<Point> for (f32, f32) {
+    fn from(point: Point) -> Self {
+        (point.x, point.y)
+    }
+}
+
+impl From<[f32; 2]> for Point {
+    fn from([x, y]: [f32; 2]) -> Self {
+        Point::new(x, y)
+    }
+}
+
+impl From<skia_safe::Point> for Point {
+    fn from(point: skia_safe::Point) -> Self {
+        Point::new(point.x, point.y)
+    }
+}
+
+impl From<Point> for skia_safe::Point {
+    fn from(point: Point) -> Self {
+        skia_safe::Point::new(point.x, point.y)
+    }
+}
+
+impl From<(f32, f32)> for Size {
+    fn from((width, height): (f32, f32)) -> Self {
+        Size::new(width, height)
+    }
+}
+
+impl From<Size> for (f32, f32) {
+    fn from(size: Size) -> Self {
+        (size.width, size.height)
+    }
+}
+
+impl From<[f32; 2]> for Size {
+    fn from([width, height]: [f32; 2]) -> Self {
+        Size::new(width, height)
+    }
+}
+
+impl From<skia_safe::Size> for Size {
+    fn from(size: skia_safe::Size) -> Self {
+        Size::new(size.width, size.height)
+    }
+}
+
+impl From<Size> for skia_safe::Size {
+    fn from(size: Size) -> Self {
+        skia_safe::Size::new(size.width, size.height)
+    }
+}
+
+/// `((x, y), (width, height))`, the pair most draw functions take
+impl From<((f32, f32), (f32, f32))> for Rect {
+    fn from((origin, size): ((f32, f32), (f32, f32))) -> Self {
+        Rect::from_origin_size(origin, size)
+    }
+}
+
+impl From<skia_safe::Rect> for Rect {
+    fn from(rect: skia_safe::Rect) -> Self {
+        Rect::from_ltrb(rect.left, rect.top, rect.right, rect.bottom)
+    }
+}
+
+impl From<Rect> for skia_safe::Rect {
+    fn from(rect: Rect) -> Self {
+        skia_safe::Rect::new(rect.left(), rect.top(), rect.right(), rect.bottom())
+    }
+}
+
+impl From<f32> for Insets {
+    fn from(inset: f32) -> Self {
+        Insets::uniform(inset)
+    }
+}
+
+/// `(horizontal, vertical)`
+impl From<(f32, f32)> for Insets {
+    fn from((horizontal, vertical): (f32, f32)) -> Self {
+        Insets::symmetric(horizontal, vertical)
+    }
+}
+
+// glam and mint have no rectangle or side offsets type, so only points and sizes convert.
+// Rects are built from converted vectors with `Rect::from_origin_size` or `Rect::from_center`.
+#[cfg(feature = "glam")]
+mod glam_conversions {
+    use super::{Point, Size};
+
+    impl From<glam::Vec2> for Point {
+        fn from(vec: glam::Vec2) -> Self {
+            Point::new(vec.x, vec.y)
+        }
+    }
+
+    impl From<Point> for glam::Vec2 {
+        fn from(point: Point) -> Self {
+            glam::Vec2::new(point.x, point.y)
+        }
+    }
+
+    impl From<glam::Vec2> for Size {
+        fn from(vec: glam::Vec2) -> Self {
+            Size::new(vec.x, vec.y)
+        }
+    }
+
+    impl From<Size> for glam::Vec2 {
+        fn from(size: Size) -> Self {
+            glam::Vec2::new(size.width, size.height)
+        }
+    }
+}
+
+#[cfg(feature = "mint")]
+mod mint_conversions {
+    use super::{Point, Size};
+
+    impl From<mint::Point2<f32>> for Point {
+        fn from(point: mint::Point2<f32>) -> Self {
+            Point::new(point.x, point.y)
+        }
+    }
+
+    impl From<Point> for mint::Point2<f32> {
+        fn from(point: Point) -> Self {
+            mint::Point2 { x: point.x, y: point.y }
+        }
+    }
+
+    impl From<mint::Vector2<f32>> for Size {
+        fn from(vec: mint::Vector2<f32>) -> Self {
+            Size::new(vec.x, vec.y)
+        }
+    }
+
+    impl From<Size> for mint::Vector2<f32> {
+        fn from(size: Size) -> Self {
+            mint::Vector2 { x: size.width, y: size.height }
+        }
+    }
+}
+
+#[cfg(feature = "euclid")]
+mod euclid_conversions {
+    use super::{Insets, Point, Rect, Size};
+
+    impl<U> From<euclid::Point2D<f32, U>> for Point {
+        fn from(point: euclid::Point2D<f32, U>) -> Self {
+            Point::new(point.x, point.y)
+        }
+    }
+
+    impl<U> From<Point> for euclid::Point2D<f32, U> {
+        fn from(point: Point) -> Self {
+            euclid::Point2D::new(point.x, point.y)
+        }
+    }
+
+    impl<U> From<euclid::Size2D<f32, U>> for Size {
+        fn from(size: euclid::Size2D<f32, U>) -> Self {
+            Size::new(size.width, size.height)
+        }
+    }
+
+    impl<U> From<Size> for euclid::Size2D<f32, U> {
+        fn from(size: Size) -> Self {
+            euclid::Size2D::new(size.width, size.height)
+        }
+    }
+
+    impl<U> From<euclid::Rect<f32, U>> for Rect {
+        fn from(rect: euclid::Rect<f32, U>) -> Self {
+            Rect::new(rect.origin.x, rect.origin.y, rect.size.width, rect.size.height)
+        }
+    }
+
+    impl<U> From<Rect> for euclid::Rect<f32, U> {
+        fn from(rect: Rect) -> Self {
+            euclid::Rect::new(rect.origin().into(), rect.size().into())
+        }
+    }
+
+    impl<U> From<euclid::SideOffsets2D<f32, U>> for Insets {
+        fn from(offsets: euclid::SideOffsets2D<f32, U>) -> Self {
+            Insets::new(offsets.top, offsets.right, offsets.bottom, offsets.left)
+        }
+    }
+
+    impl<U> From<Insets> for euclid::SideOffsets2D<f32, U> {
+        fn from(insets: Insets) -> Self {
+            euclid::SideOffsets2D::new(insets.top, insets.right, insets.bottom, insets.left)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rect_operations() {
+        let a = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let b = Rect::new(5.0, 5.0, 10.0, 10.0);
+
+        assert_eq!(a.intersection(b), Some(Rect::new(5.0, 5.0, 5.0, 5.0)));
+        assert_eq!(a.intersection(Rect::new(20.0, 0.0, 5.0, 5.0)), None);
+        assert_eq!(a.union(b), Rect::new(0.0, 0.0, 15.0, 15.0));
+        assert_eq!(a.inset(2.0), Rect::new(2.0, 2.0, 6.0, 6.0));
+        assert_eq!(a.outset((1.0, 2.0)), Rect::new(-1.0, -2.0, 12.0, 14.0));
+        assert_eq!(a.center(), Point::new(5.0, 5.0));
+        assert!(a.contains((0.0, 9.5)));
+        assert!(!a.contains((10.0, 5.0)));
+    }
+
+    #[test]
+    fn test_point_operators() {
+        let p = Point::new(1.0, 2.0) + Point::new(3.0, 4.0) * 2.0;
+        assert_eq!(p, Point::new(7.0, 10.0));
+        assert_eq!(Point::new(0.0, 0.0) + Size::new(3.0, 4.0), Point::new(3.0, 4.0));
+        assert_eq!(Point::ZERO.distance(Point::new(3.0, 4.0)), 5.0);
+    }
+
+    #[cfg(feature = "glam")]
+    #[test]
+    fn test_glam_rect() {
+        let rect = Rect::from_origin_size(glam::Vec2::new(1.0, 2.0), glam::Vec2::new(3.0, 4.0));
+        assert_eq!(rect, Rect::new(1.0, 2.0, 3.0, 4.0));
+        assert_eq!(glam::Vec2::from(rect.size()), glam::Vec2::new(3.0, 4.0));
+    }
+}
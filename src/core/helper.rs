@@ -6,7 +6,7 @@ use windows::{
     },
 
 };
-use crate::core::OverlayError;
+use crate::core::{Color, OverlayError};
 use rand::Rng;
 use skia_safe::Color4f;
 use str_crypter::{sc, decrypt_string};
@@ -45,14 +45,9 @@ pub fn find_target_window() -> Result<HWND, OverlayError> {
     }
 }
 
-/// Takes an RGBA color tuple and converts it to a Skia Color4f
-pub fn to_color_4f(color: (u8, u8, u8, u8)) -> Color4f {
-    Color4f::new(
-        color.0 as f32 / 255.0, // R
-        color.1 as f32 / 255.0, // G
-        color.2 as f32 / 255.0, // B
-        color.3 as f32 / 255.0  // A
-    )
+/// Takes any color and converts it to a Skia Color4f
+pub fn to_color_4f(color: impl Into<Color>) -> Color4f {
+    color.into().into()
 }
 
 /// Generates a random number between two ranges
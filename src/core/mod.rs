@@ -2,8 +2,51 @@ mod gl;
 mod helper;
 mod skia;
 mod draw;
-
-use skia_safe::{Color, Font, FontMgr, FontStyle};
+mod color;
+mod brush;
+mod text;
+mod font;
+mod font_rendering;
+mod paragraph;
+mod rich_text;
+mod text_cache;
+mod shaping;
+mod text_path;
+mod text_fit;
+mod geometry;
+mod theme;
+mod color_vision;
+mod contrast;
+mod adjustment;
+mod display_list;
+#[cfg(feature = "serde")]
+mod serialization;
+
+pub use color::{Color, ColorParseError, HueDirection, InterpolationSpace};
+pub use brush::Brush;
+pub use text::{
+    HorizontalAnchor, TextAnchor, TextBackground, TextDecoration, TextMetrics, TextOutline, TextShadow,
+    TextStyle, VerticalAnchor
+};
+pub use font::{FontDescriptor, FontHandle};
+pub use font_rendering::{TextEdging, TextHinting, TextRendering};
+pub use paragraph::{ParagraphMetrics, ParagraphStyle, TextAlign};
+pub use rich_text::{MarkupError, RichSpan, RichText};
+pub use text_cache::TextCacheStats;
+pub use shaping::{FontFeature, FontVariation, TextShaping};
+pub use text_path::{GlyphOrientation, PathTextAlign, PathTextLayout};
+pub use text_fit::{FitOptions, FittedText, TruncateAt};
+pub use geometry::{Insets, Point, Rect, Size};
+pub use theme::{ComponentStyle, Theme, ThemeError, ThemeTextStyle};
+pub use color_vision::{ColorVisionDeficiency, ColorVisionFilter, Palette};
+pub use contrast::{WCAG_AA, WCAG_AAA};
+pub use adjustment::ColorAdjustment;
+pub use display_list::{DisplayList, DrawCommand, DrawTarget};
+pub use skia::{SurfaceColorSpace, SurfaceColorType, SurfaceConfig};
+
+use std::path::Path;
+use std::sync::Mutex;
+use skia_safe::canvas::SaveLayerRec;
 use skia_safe::wrapper::PointerWrapper;
 use windows::{
     Win32::{
@@ -29,6 +72,9 @@ use crate::core::{
     helper::{find_target_window, generate_random_number}
 };
 use crate::core::skia::SkiaContext;
+use crate::core::font::{read_font_file, FontRegistry};
+use crate::core::text_cache::TextCache;
+use crate::core::adjustment::AdjustmentTransition;
 
 const LAYERED_WINDOW_STYLE: i32 = 0x20;
 const WINDOW_ALPHA: u8 = 0xFF;
@@ -46,19 +92,40 @@ pub struct Overlay {
     skia_context: Option<SkiaContext>,
 
     // Cache
-    font: Font,
+    fonts: FontRegistry,
+    text_cache: Mutex<TextCache>,
+
+    // Styling
+    theme: Theme,
+    color_vision: ColorVisionFilter,
+    min_text_contrast: Option<f32>,
+    color_adjustment: ColorAdjustment,
+    adjustment_transition: Option<AdjustmentTransition>,
 }
 
 impl Overlay {
-    pub fn new(font: impl AsRef<str>, size: f32) -> Self {
-        // Create a typeface using FontMgr
-        let font_mgr = FontMgr::default();
-        let typeface = font_mgr
-            .legacy_make_typeface(Some(font.as_ref()), FontStyle::normal())
-            .expect("Failed to create Typeface");
+    /// Creates an overlay whose default font is an installed family.
+    /// Falls back to the system default typeface if the family isn't installed.
+    pub fn new(font: impl AsRef<str>, size: f32) -> Result<Self, OverlayError> {
+        Ok(Self::with_fonts(FontRegistry::from_family(font.as_ref(), size)?))
+    }
+
+    /// Creates an overlay whose default font is parsed from raw TTF/OTF/TTC data
+    pub fn from_font_bytes(bytes: &[u8], collection_index: usize, size: f32) -> Result<Self, OverlayError> {
+        Ok(Self::with_fonts(FontRegistry::from_bytes(bytes, collection_index, size)?))
+    }
 
-        let font = Font::new(typeface, size); // Set font size to 48
+    /// Creates an overlay whose default font is loaded from a TTF/OTF/TTC file
+    pub fn from_font_file(
+        path: impl AsRef<Path>,
+        collection_index: usize,
+        size: f32
+    ) -> Result<Self, OverlayError> {
+        let bytes = read_font_file(path.as_ref())?;
+        Self::from_font_bytes(&bytes, collection_index, size)
+    }
 
+    fn with_fonts(fonts: FontRegistry) -> Self {
         Self {
             // Necessity
             window_handle: HWND::default(),
@@ -68,7 +135,15 @@ impl Overlay {
             skia_context: None,
 
             // Cache
-            font,
+            fonts,
+            text_cache: Mutex::new(TextCache::new()),
+
+            // Styling
+            theme: Theme::default(),
+            color_vision: ColorVisionFilter::None,
+            min_text_contrast: Some(WCAG_AA),
+            color_adjustment: ColorAdjustment::IDENTITY,
+            adjustment_transition: None,
         }
     }
 
@@ -125,6 +200,11 @@ impl Overlay {
     }
 
     pub fn startup_renderer(&mut self, vsync: bool) -> Result<(), OverlayError> {
+        self.startup_renderer_with(vsync, SurfaceConfig::default())
+    }
+
+    /// Like `startup_renderer`, drawing in the given pixel format and color space
+    pub fn startup_renderer_with(&mut self, vsync: bool, config: SurfaceConfig) -> Result<(), OverlayError> {
         let gl_context = GlContext::new(self.window_handle)
             .map_err(|_| OverlayError::GlContextSetupFailed)?;
         
@@ -140,8 +220,11 @@ impl Overlay {
         // Make GL context current before creating Skia context
         gl_context.make_current()?;
 
-        let skia_context = SkiaContext::new(1920, 1080)
-            .map_err(|_| OverlayError::SkiaContextSetupFailed)?;
+        let skia_context = SkiaContext::new(1920, 1080, config)
+            .map_err(|error| match error {
+                OverlayError::UnsupportedSurfaceColorType(_) | OverlayError::UnsupportedSurfaceConfig(_) => error,
+                _ => OverlayError::SkiaContextSetupFailed,
+            })?;
 
         self.gl_context = Some(gl_context);
         self.skia_context = Some(skia_context);
@@ -149,11 +232,24 @@ impl Overlay {
         Ok(())
     }
 
+    /// Surface format the renderer was started with, `None` before `startup_renderer`
+    pub fn surface_config(&self) -> Option<SurfaceConfig> {
+        self.skia_context.as_ref().map(SkiaContext::config)
+    }
+
     pub fn begin_scene(&mut self) -> Result<(), OverlayError> {
+        let paint = self.compositing_paint();
+
         let canvas = self.skia_context.as_mut()
             .expect("Skia context should be initialized")
             .canvas();
         canvas.clear(Color::TRANSPARENT);
+
+        // The frame is drawn into a layer that gets its opacity and filters when end_scene restores it
+        if let Some(paint) = paint {
+            canvas.save_layer(&SaveLayerRec::default().paint(&paint));
+        }
+
         Ok(())
     }
 
@@ -164,6 +260,10 @@ impl Overlay {
         let gl_context = self.gl_context.as_mut()
             .ok_or(OverlayError::FailedToCreateOpenGLContext)?;
 
+        // Composites the layer from begin_scene, if there is one
+        sk_context.canvas().restore_to_count(1);
+        sk_context.present();
+
         sk_context.gr_context.flush_and_submit();
         gl_context.swap_buffers()?;
 
@@ -175,22 +275,9 @@ impl Overlay {
         self.begin_scene()
     }
 
+    /// Width of a single line of text, see `measure_text` for full metrics
     pub fn get_text_width(&self, text: impl AsRef<str>) -> f32 {
-        let text = text.as_ref();
-        // Create buffer for glyph IDs - one per character
-        let mut glyph_ids = vec![0u16; text.len()];
-
-        // Convert text to glyph IDs
-        self.font.text_to_glyphs(text, &mut glyph_ids);
-
-        // Create array to store widths
-        let mut widths = vec![0.0; glyph_ids.len()];
-
-        // Get the widths for these glyphs
-        self.font.get_widths(&glyph_ids, &mut widths);
-
-        // Sum all widths for total
-        widths.iter().sum()
+        self.measure_text(text).width
     }
 }
 
@@ -203,6 +290,7 @@ impl Drop for Overlay {
                 // Clear the canvas
                 let canvas = skia_context.canvas();
                 canvas.clear(Color::TRANSPARENT);
+                skia_context.present();
 
                 // Flush Skia operations
                 skia_context.gr_context.flush_and_submit();
@@ -230,6 +318,8 @@ pub enum OverlayError {
 
     GlContextSetupFailed,
     SkiaContextSetupFailed,
+    UnsupportedSurfaceColorType(SurfaceColorType),
+    UnsupportedSurfaceConfig(String),
 
     VsyncControlNotSupported,
 
@@ -239,6 +329,14 @@ pub enum OverlayError {
     DrawFailed,
     DrawTextFailed(i32),
     FailedToGetFontWidth,
+    FontNotFound(String),
+    UnnamedFont(FontHandle),
+    InvalidFontHandle,
+    InvalidFontSize,
+    FailedToReadFontFile(String),
+    InvalidMarkup(MarkupError),
+    InvalidTheme(ThemeError),
+    ThemeKeyNotFound(String),
     CreateBrushFailed(i32),
     CreateSolidColorBrushFailed,
     ID2D1BrushCastFailed,
@@ -0,0 +1,290 @@
+use skia_safe::{textlayout, Canvas, FontArguments, FontHinting, Paint, Point, Rect};
+use skia_safe::font::Edging;
+use skia_safe::font_arguments::VariationPosition;
+use crate::core::{
+    Color, Overlay, OverlayError, TextStyle,
+    geometry,
+    font::FontRegistry,
+    shaping::variation_coordinates,
+    helper::to_color_4f
+};
+
+/// Horizontal alignment of lines inside a paragraph box
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+    Justify,
+}
+
+impl From<TextAlign> for textlayout::TextAlign {
+    fn from(align: TextAlign) -> Self {
+        match align {
+            TextAlign::Left => textlayout::TextAlign::Left,
+            TextAlign::Center => textlayout::TextAlign::Center,
+            TextAlign::Right => textlayout::TextAlign::Right,
+            TextAlign::Justify => textlayout::TextAlign::Justify,
+        }
+    }
+}
+
+/// Layout constraints for a wrapped block of text
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParagraphStyle {
+    /// Width of the box lines are wrapped to
+    pub width: f32,
+    pub align: TextAlign,
+    /// Line height as a multiple of the font size, `None` uses the font's own line spacing
+    pub line_spacing: Option<f32>,
+    /// Lines past this are dropped, the last visible line gets the ellipsis
+    pub max_lines: Option<usize>,
+    /// Appended to the last line when text is cut off by `max_lines`
+    pub ellipsis: Option<String>,
+}
+
+impl ParagraphStyle {
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            align: TextAlign::Left,
+            line_spacing: None,
+            max_lines: None,
+            ellipsis: None,
+        }
+    }
+}
+
+/// Size of a laid out paragraph
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParagraphMetrics {
+    /// Width of the longest line
+    pub width: f32,
+    /// Total height, use this to stack blocks
+    pub height: f32,
+    pub line_count: usize,
+    /// True if text was cut off by `max_lines`
+    pub truncated: bool,
+}
+
+/// Stroke width per point of font size that thickens glyphs about as much as Skia's own embolden
+const EMBOLDEN_STROKE: f32 = 1.0 / 28.0;
+
+/// Part of the text a paragraph is built to paint. Paints don't affect layout, so every layer lines up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParagraphLayer {
+    /// Everything in one pass, enough when no span has an outline
+    All,
+    /// Backgrounds, shadows and outlines, painted under the fill
+    Outline,
+    /// Glyph fills and decorations
+    Fill,
+}
+
+/// Shapes and wraps styled spans into a Skia paragraph ready to measure
+pub(crate) fn build_paragraph<'a>(
+    fonts: &FontRegistry,
+    spans: impl IntoIterator<Item = (&'a str, &'a TextStyle)>,
+    layout: &ParagraphStyle
+) -> textlayout::Paragraph {
+    let spans: Vec<(&str, &TextStyle)> = spans.into_iter().collect();
+    build_layer(fonts, &spans, layout, ParagraphLayer::All, Rect::from_wh(layout.width, 0.0))
+}
+
+/// Lays out and paints styled spans inside a box whose top left corner is `origin`, returning the laid out height.
+/// Backgrounds cover each line's run of text, without the padding and rounded corners of single line text.
+pub(crate) fn paint_paragraph<'a>(
+    canvas: &Canvas,
+    fonts: &FontRegistry,
+    spans: impl IntoIterator<Item = (&'a str, &'a TextStyle)>,
+    layout: &ParagraphStyle,
+    origin: Point
+) -> f32 {
+    let spans: Vec<(&str, &TextStyle)> = spans.into_iter().collect();
+    let paragraph = build_layer(fonts, &spans, layout, ParagraphLayer::All, Rect::from_wh(layout.width, 0.0));
+    let height = paragraph.height();
+
+    // Gradients span the laid out box, which is only known after the first layout
+    let bounds = Rect::from_wh(layout.width, height);
+    let has_outline = spans.iter().any(|(_, style)| style.outline.is_some());
+    let has_gradient = spans.iter().any(|(_, style)| style.brush.is_some() || style.background.is_some());
+
+    // Paint in the paragraph's own space so the gradient bounds line up
+    canvas.save();
+    canvas.translate(origin);
+
+    if has_outline {
+        build_layer(fonts, &spans, layout, ParagraphLayer::Outline, bounds).paint(canvas, Point::default());
+        build_layer(fonts, &spans, layout, ParagraphLayer::Fill, bounds).paint(canvas, Point::default());
+    } else if has_gradient {
+        build_layer(fonts, &spans, layout, ParagraphLayer::All, bounds).paint(canvas, Point::default());
+    } else {
+        paragraph.paint(canvas, Point::default());
+    }
+
+    canvas.restore();
+    height
+}
+
+fn build_layer(
+    fonts: &FontRegistry,
+    spans: &[(&str, &TextStyle)],
+    layout: &ParagraphStyle,
+    layer: ParagraphLayer,
+    bounds: Rect
+) -> textlayout::Paragraph {
+    let mut paragraph_style = textlayout::ParagraphStyle::new();
+    paragraph_style.set_text_align(layout.align.into());
+    paragraph_style.set_max_lines(layout.max_lines);
+
+    if let Some(ellipsis) = &layout.ellipsis {
+        paragraph_style.set_ellipsis(ellipsis);
+    }
+
+    // Paragraphs always use subpixel positioning and their own metrics, only hinting can be switched off
+    let hinting_off = spans.first()
+        .is_some_and(|(_, style)| fonts.get(style.font).hinting() == FontHinting::None);
+    if hinting_off {
+        paragraph_style.turn_hinting_off();
+    }
+
+    let spans: Vec<(&str, textlayout::TextStyle)> = spans.iter()
+        .map(|(text, style)| (*text, paragraph_text_style(fonts, style, layout, layer, bounds)))
+        .collect();
+
+    // The first span also decides the paragraph's default style (ellipsis, empty lines)
+    if let Some((_, text_style)) = spans.first() {
+        paragraph_style.set_text_style(text_style);
+    }
+
+    let mut builder = textlayout::ParagraphBuilder::new(&paragraph_style, fonts.font_collection());
+    for (text, text_style) in &spans {
+        builder.push_style(text_style);
+        builder.add_text(text);
+        builder.pop();
+    }
+
+    let mut paragraph = builder.build();
+    paragraph.layout(layout.width);
+    paragraph
+}
+
+fn paragraph_text_style(
+    fonts: &FontRegistry,
+    style: &TextStyle,
+    layout: &ParagraphStyle,
+    layer: ParagraphLayer,
+    bounds: Rect
+) -> textlayout::TextStyle {
+    let font = fonts.get(style.font);
+
+    let mut paint = match layer {
+        ParagraphLayer::All | ParagraphLayer::Fill => style.fill_paint(bounds),
+        // Spans without an outline still take up their space in the layer, just invisibly
+        ParagraphLayer::Outline => style.outline_paint()
+            .unwrap_or_else(|| Paint::new(to_color_4f(Color::TRANSPARENT), None)),
+    };
+    paint.set_anti_alias(font.edging() != Edging::Alias);
+
+    // Paragraph fonts are built from the typeface alone, so embolden is redone by stroking the fill
+    if font.is_embolden() && layer != ParagraphLayer::Outline {
+        paint.set_style(skia_safe::paint::Style::StrokeAndFill);
+        paint.set_stroke_width(font.size() * EMBOLDEN_STROKE);
+    }
+
+    let mut text_style = textlayout::TextStyle::new();
+    text_style.set_font_families(&fonts.paragraph_families(style.font));
+    text_style.set_font_style(font.typeface().font_style());
+    text_style.set_font_size(font.size());
+    text_style.set_foreground_paint(&paint);
+
+    // Backgrounds and shadows go under the outline when there is one
+    if layer != ParagraphLayer::Fill {
+        if let Some(background) = &style.background {
+            text_style.set_background_paint(&background.brush.to_paint(bounds));
+        }
+
+        if let Some(shadow) = style.shadow {
+            text_style.add_shadow(textlayout::TextShadow::new(
+                to_color_4f(shadow.color).to_color(),
+                shadow.offset,
+                shadow.blur as f64
+            ));
+        }
+    }
+
+    // Paragraphs place decorations themselves, from the same font metrics
+    let decoration = &style.decoration;
+    let draws_decoration = layer != ParagraphLayer::Outline;
+    let mut decoration_type = textlayout::TextDecoration::NO_DECORATION;
+    decoration_type.set(textlayout::TextDecoration::UNDERLINE, decoration.underline && draws_decoration);
+    decoration_type.set(textlayout::TextDecoration::LINE_THROUGH, decoration.strikethrough && draws_decoration);
+    decoration_type.set(textlayout::TextDecoration::OVERLINE, decoration.overline && draws_decoration);
+    text_style.set_decoration_type(decoration_type);
+    text_style.set_decoration_color(to_color_4f(decoration.color.unwrap_or(style.color)).to_color());
+
+    for feature in &style.features {
+        text_style.add_font_feature(String::from_utf8_lossy(&feature.tag), feature.value as i32);
+    }
+
+    if !style.variations.is_empty() {
+        let coordinates = variation_coordinates(&style.variations);
+        let arguments = FontArguments::new()
+            .set_variation_design_position(VariationPosition { coordinates: &coordinates });
+        text_style.set_font_arguments(&arguments);
+    }
+
+    if let Some(line_spacing) = layout.line_spacing {
+        text_style.set_height(line_spacing);
+        text_style.set_height_override(true);
+    }
+
+    text_style
+}
+
+pub(crate) fn paragraph_metrics(paragraph: &textlayout::Paragraph) -> ParagraphMetrics {
+    ParagraphMetrics {
+        width: paragraph.longest_line(),
+        height: paragraph.height(),
+        line_count: paragraph.line_number(),
+        truncated: paragraph.did_exceed_max_lines(),
+    }
+}
+
+impl Overlay {
+    // PARAGRAPH FUNCTIONS --------------------
+
+    /// Draws wrapped text inside a box whose top left corner is `pos`.
+    /// Returns the laid out height so callers can stack blocks.
+    pub fn draw_paragraph(
+        &mut self,
+        pos: impl Into<geometry::Point>,
+        text: impl AsRef<str>,
+        style: impl Into<TextStyle>,
+        layout: &ParagraphStyle
+    ) -> Result<f32, OverlayError> {
+        let pos: geometry::Point = pos.into();
+        // Text on its own plate keeps readable contrast against it, like single lines
+        let style = self.readable_on_own_plate(style.into());
+        let canvas = self.skia_context.as_mut()
+            .expect("Skia context should be initialized")
+            .canvas();
+
+        Ok(paint_paragraph(canvas, &self.fonts, [(text.as_ref(), &style)], layout, pos.into()))
+    }
+
+    /// Lays out a paragraph without drawing it
+    pub fn measure_paragraph(
+        &self,
+        text: impl AsRef<str>,
+        style: impl Into<TextStyle>,
+        layout: &ParagraphStyle
+    ) -> ParagraphMetrics {
+        let style = style.into();
+        let paragraph = build_paragraph(&self.fonts, [(text.as_ref(), &style)], layout);
+        paragraph_metrics(&paragraph)
+    }
+}
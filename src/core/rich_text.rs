@@ -0,0 +1,384 @@
+use std::fmt;
+use crate::core::{
+    Color, FontHandle, Overlay, OverlayError, ParagraphMetrics, ParagraphStyle, TextAnchor, TextMetrics, TextStyle,
+    geometry,
+    paragraph::{build_paragraph, paint_paragraph, paragraph_metrics}
+};
+
+/// A piece of rich text drawn with one style
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RichSpan {
+    pub text: String,
+    pub style: TextStyle,
+}
+
+/// A sequence of styled spans, built directly or parsed from markup with `Overlay::parse_rich_text`
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RichText {
+    pub spans: Vec<RichSpan>,
+}
+
+impl RichText {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a span, returning self so spans can be chained
+    pub fn span(mut self, text: impl Into<String>, style: impl Into<TextStyle>) -> Self {
+        self.push(text, style);
+        self
+    }
+
+    pub fn push(&mut self, text: impl Into<String>, style: impl Into<TextStyle>) {
+        self.spans.push(RichSpan { text: text.into(), style: style.into() });
+    }
+
+    /// The text of every span concatenated
+    pub fn plain_text(&self) -> String {
+        self.spans.iter().map(|span| span.text.as_str()).collect()
+    }
+}
+
+/// A markup syntax error and the byte offset in the source where it was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkupError {
+    pub offset: usize,
+    pub message: String,
+}
+
+impl MarkupError {
+    fn new(offset: usize, message: impl Into<String>) -> Self {
+        Self { offset, message: message.into() }
+    }
+}
+
+impl fmt::Display for MarkupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for MarkupError {}
+
+/// Style changes a markup span applies on top of the base style
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct MarkupStyle {
+    pub color: Option<Color>,
+    pub bold: bool,
+    pub italic: bool,
+    pub size: Option<f32>,
+    pub font: Option<String>,
+}
+
+/// A run of text from markup with the tags that were open around it
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MarkupSpan {
+    pub text: String,
+    pub style: MarkupStyle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tag {
+    Color,
+    Bold,
+    Italic,
+    Size,
+    Font,
+}
+
+impl Tag {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "color" => Some(Tag::Color),
+            "b" => Some(Tag::Bold),
+            "i" => Some(Tag::Italic),
+            "size" => Some(Tag::Size),
+            "font" => Some(Tag::Font),
+            _ => None,
+        }
+    }
+}
+
+/// Parses markup like `HP: <color=#ff3300><b>87</b></color> / 100` into styled spans.
+///
+/// Supported tags are `<color=...>` with a hex color or CSS name, `<b>`, `<i>`, `<size=20>` and `<font=name>`,
+/// each closed by its matching `</tag>`. A literal `<` is written as `<<`.
+/// `font_exists` is asked about every `<font=...>` name so unknown fonts are reported where they appear.
+pub(crate) fn parse_markup(
+    markup: &str,
+    font_exists: impl Fn(&str) -> bool
+) -> Result<Vec<MarkupSpan>, MarkupError> {
+    let mut spans: Vec<MarkupSpan> = Vec::new();
+    let mut stack: Vec<(Tag, usize, MarkupStyle)> = Vec::new();
+    let mut style = MarkupStyle::default();
+    let mut text = String::new();
+    let mut rest = markup;
+
+    while let Some(open) = rest.find('<') {
+        text.push_str(&rest[..open]);
+        let offset = markup.len() - rest.len() + open;
+        rest = &rest[open + 1..];
+
+        // Escaped `<<`
+        if let Some(stripped) = rest.strip_prefix('<') {
+            text.push('<');
+            rest = stripped;
+            continue;
+        }
+
+        let close = rest.find('>')
+            .ok_or_else(|| MarkupError::new(offset, "unterminated tag"))?;
+        let tag = &rest[..close];
+        rest = &rest[close + 1..];
+
+        // Every tag changes the style, so flush the text collected so far
+        if !text.is_empty() {
+            spans.push(MarkupSpan { text: std::mem::take(&mut text), style: style.clone() });
+        }
+
+        if let Some(name) = tag.strip_prefix('/') {
+            let kind = Tag::from_name(name)
+                .ok_or_else(|| MarkupError::new(offset, format!("unknown tag '{}'", name)))?;
+
+            match stack.pop() {
+                Some((open_kind, _, previous)) if open_kind == kind => style = previous,
+                Some((open_kind, open_offset, _)) => return Err(MarkupError::new(
+                    offset,
+                    format!("'</{}>' closes '{:?}' opened at byte {}", name, open_kind, open_offset)
+                )),
+                None => return Err(MarkupError::new(offset, format!("'</{}>' has no opening tag", name))),
+            }
+            continue;
+        }
+
+        let (name, value) = match tag.split_once('=') {
+            Some((name, value)) => (name, Some(value.trim_matches('"'))),
+            None => (tag, None),
+        };
+        let kind = Tag::from_name(name)
+            .ok_or_else(|| MarkupError::new(offset, format!("unknown tag '{}'", name)))?;
+
+        stack.push((kind, offset, style.clone()));
+
+        match (kind, value) {
+            (Tag::Bold, None) => style.bold = true,
+            (Tag::Italic, None) => style.italic = true,
+            (Tag::Color, Some(value)) => {
+                style.color = Some(value.parse::<Color>()
+                    .map_err(|_| MarkupError::new(offset, format!("invalid color '{}'", value)))?);
+            }
+            (Tag::Size, Some(value)) => {
+                let size = value.parse::<f32>().ok()
+                    .filter(|size| size.is_finite() && *size > 0.0)
+                    .ok_or_else(|| MarkupError::new(offset, format!("invalid size '{}'", value)))?;
+                style.size = Some(size);
+            }
+            (Tag::Font, Some(value)) => {
+                if !font_exists(value) {
+                    return Err(MarkupError::new(offset, format!("unknown font '{}'", value)));
+                }
+                style.font = Some(value.to_string());
+            }
+            (Tag::Bold | Tag::Italic, Some(_)) => {
+                return Err(MarkupError::new(offset, format!("'<{}>' doesn't take a value", name)));
+            }
+            (_, None) => return Err(MarkupError::new(offset, format!("'<{}>' needs a value", name))),
+        }
+    }
+
+    if let Some((kind, offset, _)) = stack.pop() {
+        return Err(MarkupError::new(offset, format!("'{:?}' tag is never closed", kind)));
+    }
+
+    text.push_str(rest);
+    if !text.is_empty() {
+        spans.push(MarkupSpan { text, style });
+    }
+
+    Ok(spans)
+}
+
+/// The base style with a markup span's color applied, drawn with `font` that already has its bold, italic and size
+fn span_style(base: &TextStyle, markup: &MarkupStyle, font: FontHandle) -> TextStyle {
+    TextStyle {
+        font,
+        color: markup.color.unwrap_or(base.color),
+        // A `<color>` tag overrides a gradient fill too
+        brush: base.brush.clone().filter(|_| markup.color.is_none()),
+        ..base.clone()
+    }
+}
+
+impl Overlay {
+    // RICH TEXT FUNCTIONS --------------------
+
+    /// Parses markup into rich text, resolving `<b>`, `<i>`, `<size>` and `<font>` into registered fonts.
+    /// Parse once and keep the result for text that doesn't change every frame.
+    pub fn parse_rich_text(
+        &mut self,
+        markup: impl AsRef<str>,
+        base: impl Into<TextStyle>
+    ) -> Result<RichText, OverlayError> {
+        let base = base.into();
+        let spans = parse_markup(markup.as_ref(), |name| self.fonts.by_name(name).is_some())
+            .map_err(OverlayError::InvalidMarkup)?;
+
+        let mut rich_text = RichText::new();
+        for span in spans {
+            let font = span.style.font.as_deref()
+                .and_then(|name| self.fonts.by_name(name))
+                .unwrap_or(base.font);
+
+            let font = self.fonts.variant(font, span.style.bold, span.style.italic, span.style.size);
+            rich_text.push(span.text, span_style(&base, &span.style, font));
+        }
+
+        Ok(rich_text)
+    }
+
+    /// Draws rich text on a single baseline, positioned by `anchor` as a whole.
+    /// Span anchors are ignored. Returns the advance by ascent+descent box the text occupies.
+    pub fn draw_rich_text(
+        &mut self,
+        pos: impl Into<geometry::Point>,
+        text: &RichText,
+        anchor: TextAnchor
+    ) -> Result<geometry::Rect, OverlayError> {
+        let pos: geometry::Point = pos.into();
+        let metrics = self.measure_rich_text(text);
+        let (x, y) = anchor.baseline_origin(pos.into(), &metrics);
+        let mut advance = 0.0;
+
+        for span in &text.spans {
+            let style = span.style.clone().with_anchor(TextAnchor::BASELINE_LEFT);
+            let bounds = self.draw_text_with((x + advance, y), &span.text, style)?;
+            advance += bounds.width;
+        }
+
+        Ok(metrics.layout_bounds((x, y)).into())
+    }
+
+    /// Measures rich text laid out on a single baseline
+    pub fn measure_rich_text(&self, text: &RichText) -> TextMetrics {
+        let mut result: Option<TextMetrics> = None;
+
+        for span in &text.spans {
+            let metrics = self.measure_text_with(&span.text, &span.style);
+
+            result = Some(match result {
+                None => metrics,
+                Some(mut total) => {
+                    total.bounds.join(metrics.bounds.with_offset((total.width, 0.0)));
+                    total.width += metrics.width;
+                    total.ascent = total.ascent.max(metrics.ascent);
+                    total.descent = total.descent.max(metrics.descent);
+                    total.leading = total.leading.max(metrics.leading);
+                    total.line_height = total.line_height.max(metrics.line_height);
+                    total
+                }
+            });
+        }
+
+        result.unwrap_or_else(|| self.measure_text(""))
+    }
+
+    /// Draws rich text wrapped inside a box whose top left corner is `pos`. Returns the laid out height.
+    pub fn draw_rich_paragraph(
+        &mut self,
+        pos: impl Into<geometry::Point>,
+        text: &RichText,
+        layout: &ParagraphStyle
+    ) -> Result<f32, OverlayError> {
+        let pos: geometry::Point = pos.into();
+        let styles: Vec<TextStyle> = text.spans.iter()
+            .map(|span| self.readable_on_own_plate(span.style.clone()))
+            .collect();
+        let canvas = self.skia_context.as_mut()
+            .expect("Skia context should be initialized")
+            .canvas();
+
+        let spans = text.spans.iter().zip(&styles).map(|(span, style)| (span.text.as_str(), style));
+        Ok(paint_paragraph(canvas, &self.fonts, spans, layout, pos.into()))
+    }
+
+    /// Lays out wrapped rich text without drawing it
+    pub fn measure_rich_paragraph(&self, text: &RichText, layout: &ParagraphStyle) -> ParagraphMetrics {
+        let spans = text.spans.iter().map(|span| (span.text.as_str(), &span.style));
+        paragraph_metrics(&build_paragraph(&self.fonts, spans, layout))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::Brush;
+    use super::*;
+
+    #[test]
+    fn test_parse_markup() {
+        let spans = parse_markup("HP: <color=#ff3300><b>87</b></color> / 100", |_| true).unwrap();
+
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans[0].text, "HP: ");
+        assert_eq!(spans[0].style, MarkupStyle::default());
+        assert_eq!(spans[1].text, "87");
+        assert_eq!(spans[1].style.color, Some(Color::rgb(255, 51, 0)));
+        assert!(spans[1].style.bold);
+        assert_eq!(spans[2].text, " / 100");
+        assert_eq!(spans[2].style, MarkupStyle::default());
+    }
+
+    #[test]
+    fn test_parse_markup_escapes_and_values() {
+        let spans = parse_markup("a <<b> <size=20><font=mono>c</font></size>", |name| name == "mono").unwrap();
+
+        assert_eq!(spans[0].text, "a <b> ");
+        assert_eq!(spans[1].style.size, Some(20.0));
+        assert_eq!(spans[1].style.font.as_deref(), Some("mono"));
+    }
+
+    #[test]
+    fn test_parse_markup_errors() {
+        let error = parse_markup("ab<b>cd</i>", |_| true).unwrap_err();
+        assert_eq!(error.offset, 7);
+
+        let error = parse_markup("ab<b>cd", |_| true).unwrap_err();
+        assert_eq!(error.offset, 2);
+
+        let error = parse_markup("é<color=#zzz>x</color>", |_| true).unwrap_err();
+        assert_eq!(error.offset, 2);
+
+        let error = parse_markup("<font=mono>x</font>", |_| false).unwrap_err();
+        assert_eq!(error.offset, 0);
+
+        let error = parse_markup("x <size=12", |_| true).unwrap_err();
+        assert_eq!(error.offset, 2);
+    }
+
+    #[test]
+    fn test_span_style() {
+        let base = TextStyle::new(FontHandle::DEFAULT, Color::WHITE)
+            .with_brush(Brush::linear_gradient(Color::RED, Color::BLACK, false))
+            .with_underline();
+
+        let plain = span_style(&base, &MarkupStyle::default(), FontHandle(3));
+        assert_eq!(plain, TextStyle { font: FontHandle(3), ..base.clone() });
+
+        let colored = MarkupStyle { color: Some(Color::BLUE), ..Default::default() };
+        let colored = span_style(&base, &colored, FontHandle::DEFAULT);
+        assert_eq!(colored.color, Color::BLUE);
+        assert_eq!(colored.brush, None);
+        assert!(colored.decoration.underline);
+    }
+
+    #[test]
+    fn test_plain_text() {
+        let text = RichText::new()
+            .span("HP: ", FontHandle::DEFAULT)
+            .span("87", TextStyle::new(FontHandle::DEFAULT, Color::RED));
+
+        assert_eq!(text.spans.len(), 2);
+        assert_eq!(text.plain_text(), "HP: 87");
+    }
+}
@@ -0,0 +1,178 @@
+use std::borrow::Cow;
+use std::fmt;
+use serde::de::{MapAccess, SeqAccess, Visitor};
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+use crate::core::{Color, DisplayList, DrawCommand, FontDescriptor, FontHandle};
+
+/// Colors are `"#rrggbbaa"` in text formats like JSON and four bytes in binary ones
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.to_hex())
+        } else {
+            [self.r, self.g, self.b, self.a].serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let hex = Cow::<str>::deserialize(deserializer)?;
+            Color::from_hex(&hex).map_err(de::Error::custom)
+        } else {
+            let [r, g, b, a] = <[u8; 4]>::deserialize(deserializer)?;
+            Ok(Color::rgba(r, g, b, a))
+        }
+    }
+}
+
+/// On-disk form of a display list: the format version first, so newer lists are refused before their
+/// commands are decoded, then names for the fonts the commands use
+#[derive(Serialize)]
+struct VersionedList<'a> {
+    version: u32,
+    fonts: &'a [(FontHandle, FontDescriptor)],
+    commands: &'a [DrawCommand],
+}
+
+impl Serialize for DisplayList {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // A bare handle is an index into one overlay's fonts and would pick an arbitrary font anywhere else
+        let undescribed = self.used_fonts().into_iter()
+            .find(|handle| *handle != FontHandle::DEFAULT && !self.fonts().iter().any(|(described, _)| described == handle));
+        if let Some(handle) = undescribed {
+            return Err(ser::Error::custom(format!(
+                "{:?} has no name, call Overlay::describe_display_list_fonts before serializing",
+                handle
+            )));
+        }
+
+        VersionedList { version: DisplayList::FORMAT_VERSION, fonts: self.fonts(), commands: self.commands() }
+            .serialize(serializer)
+    }
+}
+
+const FIELDS: &[&str] = &["version", "fonts", "commands"];
+
+fn check_version<E: de::Error>(version: u32) -> Result<(), E> {
+    if version > DisplayList::FORMAT_VERSION {
+        return Err(E::custom(format!(
+            "display list format {} is newer than the supported {}",
+            version,
+            DisplayList::FORMAT_VERSION
+        )));
+    }
+    Ok(())
+}
+
+struct DisplayListVisitor;
+
+impl<'de> Visitor<'de> for DisplayListVisitor {
+    type Value = DisplayList;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a display list with its format version first")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<DisplayList, A::Error> {
+        let version = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        check_version(version)?;
+
+        let fonts = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        let commands = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(2, &self))?;
+        Ok(DisplayList::from_parts(commands, fonts))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<DisplayList, A::Error> {
+        if map.next_key::<String>()?.as_deref() != Some("version") {
+            return Err(de::Error::custom("display list version has to come before its fonts and commands"));
+        }
+        check_version(map.next_value()?)?;
+
+        let mut fonts = None;
+        let mut commands = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "fonts" if fonts.is_none() => fonts = Some(map.next_value()?),
+                "commands" if commands.is_none() => commands = Some(map.next_value()?),
+                "version" | "fonts" | "commands" => return Err(de::Error::custom(format!("duplicate field `{}`", key))),
+                other => return Err(de::Error::unknown_field(other, FIELDS)),
+            }
+        }
+
+        let commands = commands.ok_or_else(|| de::Error::missing_field("commands"))?;
+        Ok(DisplayList::from_parts(commands, fonts.unwrap_or_default()))
+    }
+}
+
+impl<'de> Deserialize<'de> for DisplayList {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct("DisplayList", FIELDS, DisplayListVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{
+        Brush, FitOptions, FontHandle, InterpolationSpace, ParagraphStyle, RichText, TextAnchor, TextStyle, TruncateAt
+    };
+    use super::*;
+
+    fn scene() -> DisplayList {
+        let style = TextStyle::new(FontHandle::DEFAULT, Color::WHITE)
+            .with_outline(1.5, Color::BLACK)
+            .with_shadow((1.0, 2.0), 3.0, Color::rgba(0, 0, 0, 128))
+            .with_background(Brush::linear_gradient(Color::RED, Color::BLUE, true), (4.0, 2.0), 3.0)
+            .with_underline()
+            .with_anchor(TextAnchor::CENTER);
+
+        let mut list = DisplayList::new();
+        list.draw_filled_rounded_rect((10.0, 20.0), (200.0, 80.0), 6.0, Color::rgba(20, 20, 30, 200));
+        list.draw_gradient_line_in((0.0, 0.0), (50.5, 25.25), 2.0, Color::RED, Color::LIME, InterpolationSpace::Oklab);
+        list.draw_text_with((110.0, 60.0), "Health: 100", style.clone());
+        list.draw_fitted_text((0.0, 0.0), "A long player name", &style, &FitOptions {
+            truncate: Some(TruncateAt::Middle),
+            ..FitOptions::new(80.0)
+        });
+        list.draw_rich_paragraph(
+            (5.0, 5.0),
+            &RichText::new().span("Paused ", &style).span("press Esc", FontHandle::DEFAULT),
+            &ParagraphStyle::new(300.0)
+        );
+        list.draw_label((0.0, 0.0), "Ready", "label");
+        list
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let list = scene();
+
+        let json = serde_json::to_string(&list).unwrap();
+        assert!(json.starts_with(r#"{"version":1,"#));
+        assert!(json.contains(r##""#ff0000ff""##));
+        assert_eq!(serde_json::from_str::<DisplayList>(&json).unwrap(), list);
+
+        let bytes = bincode::serialize(&list).unwrap();
+        assert!(bytes.len() < json.len());
+        assert_eq!(bincode::deserialize::<DisplayList>(&bytes).unwrap(), list);
+
+        // Lists from a newer version are refused before their commands are decoded
+        let newer = r#"{"version":2,"fonts":[],"commands":[{"SomeFutureCommand":{}}]}"#;
+        let error = serde_json::from_str::<DisplayList>(newer).unwrap_err().to_string();
+        assert!(error.contains("newer than the supported"), "{}", error);
+    }
+
+    #[test]
+    fn test_fonts_need_names() {
+        let mut list = DisplayList::new();
+        list.draw_text_with((0.0, 0.0), "Score", TextStyle::new(FontHandle(3), Color::WHITE));
+        assert!(serde_json::to_string(&list).is_err());
+
+        let descriptor = FontDescriptor { name: Some("mono".to_string()), bold: true, italic: false, size: 18.0 };
+        let list = DisplayList::from_parts(list.commands().to_vec(), vec![(FontHandle(3), descriptor)]);
+        let json = serde_json::to_string(&list).unwrap();
+        assert!(json.contains(r#""name":"mono""#));
+        assert_eq!(serde_json::from_str::<DisplayList>(&json).unwrap(), list);
+    }
+}
@@ -0,0 +1,427 @@
+use skia_safe::{Font, FontArguments, FourByteTag, GlyphId, Point, Rect, TextBlob, Typeface};
+use skia_safe::font_arguments::{variation_position::Coordinate, VariationPosition};
+use skia_safe::shaper::run_handler::{Buffer, RunHandler, RunInfo};
+use skia_safe::shaper::{Feature, RunIterator};
+use skia_safe::shapers::primitive::trivial_bidi_run_iterator;
+use skia_safe::Shaper;
+use std::ops::Range;
+use crate::core::{TextMetrics, font::FontRegistry, text_cache::{ShapeKey, ShapedText}};
+
+/// How characters are turned into glyphs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextShaping {
+    /// One glyph per character in logical order. Fastest, fine for Latin, Cyrillic, Greek and CJK.
+    /// Text with OpenType features is shaped like `LeftToRight` instead.
+    #[default]
+    Simple,
+    /// HarfBuzz shaping with bidi reordering for a left-to-right paragraph.
+    /// Needed for Arabic, Hebrew, Indic scripts, ligatures and OpenType features.
+    LeftToRight,
+    /// Same as `LeftToRight` but the paragraph's base direction is right-to-left
+    RightToLeft,
+}
+
+/// An OpenType feature set while shaping, like `tnum` for digits that all have the same advance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontFeature {
+    pub tag: [u8; 4],
+    /// 0 turns the feature off, 1 on, higher values pick an alternate
+    pub value: u32,
+}
+
+impl FontFeature {
+    /// Digits share one advance, so changing numbers don't shift sideways
+    pub const TABULAR_NUMBERS: FontFeature = FontFeature::new(*b"tnum", 1);
+    pub const NO_LIGATURES: FontFeature = FontFeature::new(*b"liga", 0);
+    pub const SLASHED_ZERO: FontFeature = FontFeature::new(*b"zero", 1);
+    pub const SMALL_CAPS: FontFeature = FontFeature::new(*b"smcp", 1);
+
+    pub const fn new(tag: [u8; 4], value: u32) -> Self {
+        Self { tag, value }
+    }
+
+    fn to_feature(self, text_len: usize) -> Feature {
+        Feature {
+            tag: u32::from_be_bytes(self.tag),
+            value: self.value,
+            start: 0,
+            end: text_len,
+        }
+    }
+}
+
+/// A position on one axis of a variable font, like weight 650
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontVariation {
+    pub axis: [u8; 4],
+    pub value: f32,
+}
+
+impl FontVariation {
+    pub const fn new(axis: [u8; 4], value: f32) -> Self {
+        Self { axis, value }
+    }
+
+    /// `wght` axis, 100 to 900 with 400 being regular
+    pub const fn weight(value: f32) -> Self {
+        Self::new(*b"wght", value)
+    }
+
+    /// `wdth` axis, percent of the normal width
+    pub const fn width(value: f32) -> Self {
+        Self::new(*b"wdth", value)
+    }
+}
+
+// Compared bitwise so variations can be part of the shaped text cache key
+impl Eq for FontVariation {}
+
+impl std::hash::Hash for FontVariation {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.axis.hash(state);
+        self.value.to_bits().hash(state);
+    }
+}
+
+pub(crate) fn variation_coordinates(variations: &[FontVariation]) -> Vec<Coordinate> {
+    variations.iter()
+        .map(|variation| Coordinate {
+            axis: FourByteTag::new(u32::from_be_bytes(variation.axis)),
+            value: variation.value,
+        })
+        .collect()
+}
+
+/// Copy of a font with variable axes moved. Fonts without those axes keep their default design.
+pub(crate) fn with_variations(font: &Font, variations: &[FontVariation]) -> Font {
+    if variations.is_empty() {
+        return font.clone();
+    }
+
+    let coordinates = variation_coordinates(variations);
+    let arguments = FontArguments::new()
+        .set_variation_design_position(VariationPosition { coordinates: &coordinates });
+
+    let mut font = font.clone();
+    if let Some(typeface) = font.typeface().clone_with_arguments(&arguments) {
+        font.set_typeface(typeface);
+    }
+    font
+}
+
+/// Glyphs of one shaped run, positioned relative to the left baseline of the line
+pub(crate) struct GlyphRun {
+    pub font: Font,
+    pub glyphs: Vec<GlyphId>,
+    pub positions: Vec<Point>,
+}
+
+/// Collects the shaper's output in visual order instead of building a blob straight away,
+/// so advances and ink bounds can be measured exactly
+#[derive(Default)]
+struct GlyphRunCollector {
+    runs: Vec<GlyphRun>,
+    advance: f32,
+}
+
+impl RunHandler for GlyphRunCollector {
+    fn begin_line(&mut self) {}
+
+    fn run_info(&mut self, _info: &RunInfo) {}
+
+    fn commit_run_info(&mut self) {}
+
+    fn run_buffer(&mut self, info: &RunInfo) -> Buffer {
+        self.runs.push(GlyphRun {
+            font: info.font.clone(),
+            glyphs: vec![0; info.glyph_count],
+            positions: vec![Point::default(); info.glyph_count],
+        });
+
+        let run = self.runs.last_mut().expect("run was just pushed");
+        Buffer::new(&mut run.glyphs, &mut run.positions, Point::new(self.advance, 0.0))
+    }
+
+    fn commit_run_buffer(&mut self, info: &RunInfo) {
+        self.advance += info.advance.x;
+    }
+
+    fn commit_line(&mut self) {}
+}
+
+/// Stretch of a line with a single bidi level and a single font, `font` indexes the line's font runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LinePiece {
+    range: Range<usize>,
+    level: u8,
+    font: usize,
+}
+
+/// Cuts a line into pieces where both the bidi run and the font run stay the same, in logical order
+fn line_pieces(bidi_runs: &[(Range<usize>, u8)], font_runs: &[Range<usize>]) -> Vec<LinePiece> {
+    let mut pieces = Vec::new();
+    for (range, level) in bidi_runs {
+        for (font, font_range) in font_runs.iter().enumerate() {
+            let start = range.start.max(font_range.start);
+            let end = range.end.min(font_range.end);
+            if start < end {
+                pieces.push(LinePiece { range: start..end, level: *level, font });
+            }
+        }
+    }
+    pieces
+}
+
+/// Visual order of runs from their bidi levels. From the highest level down to the lowest odd one,
+/// every sequence of runs at that level or above is reversed (rule L2 of UAX #9).
+fn visual_order(levels: &[u8]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..levels.len()).collect();
+    let Some(&highest) = levels.iter().max() else {
+        return order;
+    };
+    let lowest_odd = levels.iter().copied().filter(|level| level % 2 == 1).min().unwrap_or(highest + 1);
+
+    for level in (lowest_odd..=highest).rev() {
+        let mut index = 0;
+        while index < order.len() {
+            if levels[order[index]] < level {
+                index += 1;
+                continue;
+            }
+
+            let start = index;
+            while index < order.len() && levels[order[index]] >= level {
+                index += 1;
+            }
+            order[start..index].reverse();
+        }
+    }
+    order
+}
+
+/// Bidi runs of a whole line with their embedding levels, in logical order
+fn bidi_runs(text: &str, base_level: u8) -> Option<Vec<(Range<usize>, u8)>> {
+    let mut iterator = Shaper::new_bidi_run_iterator(text, base_level)?;
+    let mut runs = Vec::new();
+    let mut start = 0;
+    while !iterator.at_end() {
+        iterator.consume();
+        let end = iterator.end_of_current_run();
+        runs.push((start..end, iterator.current_level()));
+        start = end;
+    }
+    Some(runs)
+}
+
+/// Shapes a single line with HarfBuzz into glyph runs in visual order, plus the total advance
+fn shape_runs(fonts: &FontRegistry, primary: &Font, key: &ShapeKey) -> (Vec<GlyphRun>, f32) {
+    let text = key.text.as_str();
+    let mut collector = GlyphRunCollector::default();
+    let base_level = if key.shaping == TextShaping::RightToLeft { 1 } else { 0 };
+
+    // Levels are resolved over the whole line, so spaces between two words of a fallback font
+    // take the direction of those words rather than splitting them into separate paragraphs
+    let Some(levels) = bidi_runs(text, base_level) else {
+        log::warn!("HarfBuzz run iterators are unavailable, shaping text one glyph per character");
+        return simple_runs(fonts, primary, text);
+    };
+
+    // HarfBuzz only falls back to the system font manager, so the user fallback chain is applied
+    // inside each bidi run and every piece is shaped on its own, then laid out in visual order
+    let segments = fonts.font_runs(primary, text);
+    let ranges: Vec<Range<usize>> = segments.iter().map(|segment| segment.range.clone()).collect();
+    let pieces = line_pieces(&levels, &ranges);
+    let piece_levels: Vec<u8> = pieces.iter().map(|piece| piece.level).collect();
+
+    for index in visual_order(&piece_levels) {
+        let piece = &pieces[index];
+        let piece_text = &text[piece.range.clone()];
+        let features: Vec<Feature> = key.features.iter().map(|feature| feature.to_feature(piece_text.len())).collect();
+
+        let mut font_runs = Shaper::new_font_mgr_run_iterator(piece_text, &segments[piece.font].font, fonts.system_font_mgr());
+        let mut bidi_runs = trivial_bidi_run_iterator(piece.level, piece_text.len());
+        let mut script_runs = Shaper::new_hb_icu_script_run_iterator(piece_text);
+        let Some(mut language_runs) = Shaper::new_std_language_run_iterator(piece_text) else {
+            log::warn!("HarfBuzz run iterators are unavailable, shaping text one glyph per character");
+            return simple_runs(fonts, primary, text);
+        };
+
+        // Unbounded width keeps everything on one line, the collector carries the advance across pieces
+        fonts.shaper().shape_with_iterators_and_features(
+            piece_text,
+            &mut font_runs,
+            &mut bidi_runs,
+            &mut script_runs,
+            &mut language_runs,
+            &features,
+            f32::INFINITY,
+            &mut collector
+        );
+    }
+
+    let runs = collector.runs.into_iter().filter(|run| !run.glyphs.is_empty()).collect();
+    (runs, collector.advance)
+}
+
+/// Whether a key has to go through HarfBuzz rather than the one glyph per character path
+pub(crate) fn needs_shaper(key: &ShapeKey) -> bool {
+    key.shaping != TextShaping::Simple || !key.features.is_empty()
+}
+
+/// Glyph runs of a single line in the style's shaping mode, for callers that place glyphs themselves
+pub(crate) fn glyph_runs(fonts: &FontRegistry, key: &ShapeKey) -> Vec<GlyphRun> {
+    let primary = with_variations(fonts.get(key.font), &key.variations);
+    if needs_shaper(key) {
+        return shape_runs(fonts, &primary, key).0;
+    }
+
+    simple_runs(fonts, &primary, &key.text).0
+}
+
+/// One glyph per character in logical order, plus the total advance
+fn simple_runs(fonts: &FontRegistry, primary: &Font, text: &str) -> (Vec<GlyphRun>, f32) {
+    let mut runs = Vec::new();
+    let mut advance = 0.0;
+    for run in fonts.font_runs(primary, text) {
+        let glyphs = run.font.str_to_glyphs_vec(&text[run.range]);
+        let mut widths = vec![0.0; glyphs.len()];
+        run.font.get_widths(&glyphs, &mut widths);
+
+        let mut positions = Vec::with_capacity(glyphs.len());
+        for width in widths {
+            positions.push(Point::new(advance, 0.0));
+            advance += width;
+        }
+
+        if !glyphs.is_empty() {
+            runs.push(GlyphRun { font: run.font, glyphs, positions });
+        }
+    }
+
+    (runs, advance)
+}
+
+/// Shapes a single line with HarfBuzz, resolving bidi runs and fallback fonts through the registry
+pub(crate) fn shape_complex(fonts: &FontRegistry, primary: &Font, key: &ShapeKey) -> ShapedText {
+    let (runs, advance) = shape_runs(fonts, primary, key);
+
+    let (line_height, metrics) = primary.metrics();
+    let mut result = TextMetrics {
+        width: advance,
+        bounds: Rect::new_empty(),
+        ascent: -metrics.ascent,
+        descent: metrics.descent,
+        leading: metrics.leading,
+        line_height,
+    };
+
+    let mut blobs = Vec::new();
+    for run in runs {
+        let mut glyph_bounds = vec![Rect::default(); run.glyphs.len()];
+        run.font.get_bounds(&run.glyphs, &mut glyph_bounds, None);
+
+        for (bounds, position) in glyph_bounds.iter().zip(&run.positions) {
+            result.bounds.join(bounds.with_offset(*position));
+        }
+
+        // Fallback fonts can be taller than the primary font, the line has to fit them
+        if !Typeface::equal(run.font.typeface(), primary.typeface()) {
+            let (run_line_height, run_metrics) = run.font.metrics();
+            result.ascent = result.ascent.max(-run_metrics.ascent);
+            result.descent = result.descent.max(run_metrics.descent);
+            result.line_height = result.line_height.max(run_line_height);
+        }
+
+        if let Some(text_blob) = TextBlob::from_pos_text(run.glyphs.as_slice(), &run.positions, &run.font) {
+            blobs.push((text_blob, Point::default()));
+        }
+    }
+
+    ShapedText { blobs, metrics: result }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn visual_ranges(bidi_runs: &[(Range<usize>, u8)], font_runs: &[Range<usize>]) -> Vec<Range<usize>> {
+        let pieces = line_pieces(bidi_runs, font_runs);
+        let levels: Vec<u8> = pieces.iter().map(|piece| piece.level).collect();
+        visual_order(&levels).into_iter().map(|index| pieces[index].range.clone()).collect()
+    }
+
+    #[test]
+    fn test_visual_order() {
+        assert_eq!(visual_order(&[]), Vec::<usize>::new());
+        assert_eq!(visual_order(&[0, 0, 0]), vec![0, 1, 2]);
+        assert_eq!(visual_order(&[1, 1, 1]), vec![2, 1, 0]);
+        assert_eq!(visual_order(&[0, 1, 1, 0]), vec![0, 2, 1, 3]);
+        // Numbers inside right-to-left text keep their own order
+        assert_eq!(visual_order(&[1, 2, 2, 1]), vec![3, 1, 2, 0]);
+    }
+
+    #[test]
+    fn test_rtl_words_in_ltr_line() {
+        // Two Hebrew words in a fallback font under LeftToRight. The bidi pass puts the space
+        // between them at level 1 too, so the words swap places as a whole.
+        let text = "\u{5d0}\u{5d1} \u{5d2}\u{5d3}";
+        let first = 0..4;
+        let space = 4..5;
+        let second = 5..text.len();
+
+        let bidi = [(0..text.len(), 1)];
+        let fonts = [first.clone(), space.clone(), second.clone()];
+        assert_eq!(visual_ranges(&bidi, &fonts), vec![second, space, first]);
+    }
+
+    #[test]
+    fn test_mixed_directions() {
+        // "ab \u{5d0}\u{5d1} \u{5d2}\u{5d3} cd" in a left-to-right line, Hebrew in a fallback font
+        let bidi = [(0..3, 0), (3..12, 1), (12..15, 0)];
+        let fonts = [0..3, 3..7, 7..8, 8..12, 12..15];
+        assert_eq!(visual_ranges(&bidi, &fonts), vec![0..3, 8..12, 7..8, 3..7, 12..15]);
+
+        // The same pieces in a right-to-left line, the Latin runs sit at level 2 and stay readable
+        let bidi = [(0..2, 2), (2..13, 1), (13..15, 2)];
+        let fonts = [0..3, 3..7, 7..8, 8..12, 12..15];
+        assert_eq!(
+            visual_ranges(&bidi, &fonts),
+            vec![13..15, 12..13, 8..12, 7..8, 3..7, 2..3, 0..2]
+        );
+    }
+
+    #[test]
+    fn test_line_pieces() {
+        let pieces = line_pieces(&[(0..4, 0), (4..8, 1)], &[0..2, 2..6, 6..8]);
+        assert_eq!(pieces, vec![
+            LinePiece { range: 0..2, level: 0, font: 0 },
+            LinePiece { range: 2..4, level: 0, font: 1 },
+            LinePiece { range: 4..6, level: 1, font: 1 },
+            LinePiece { range: 6..8, level: 1, font: 2 },
+        ]);
+    }
+
+    #[test]
+    fn test_features() {
+        let feature = FontFeature::TABULAR_NUMBERS.to_feature(12);
+        assert_eq!(feature.tag, u32::from_be_bytes(*b"tnum"));
+        assert_eq!((feature.value, feature.start, feature.end), (1, 0, 12));
+        assert_eq!(FontFeature::NO_LIGATURES.to_feature(3).value, 0);
+
+        let coordinates = variation_coordinates(&[FontVariation::weight(650.0), FontVariation::width(80.0)]);
+        assert_eq!(coordinates.len(), 2);
+        assert_eq!(coordinates[0].axis, FourByteTag::new(u32::from_be_bytes(*b"wght")));
+        assert_eq!(coordinates[1].value, 80.0);
+    }
+
+    #[test]
+    fn test_needs_shaper() {
+        let style = crate::core::TextStyle::default();
+        assert!(!needs_shaper(&ShapeKey::new("12", &style)));
+        assert!(needs_shaper(&ShapeKey::new("12", &style.clone().with_tabular_numbers())));
+        assert!(needs_shaper(&ShapeKey::new("12", &style.with_shaping(TextShaping::RightToLeft))));
+    }
+}
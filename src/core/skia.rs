@@ -1,23 +1,128 @@
-
-use skia_safe::gpu::{backend_render_targets, surfaces, DirectContext, Protected, SurfaceOrigin};
+use skia_safe::gpu::{backend_render_targets, surfaces, Budgeted, DirectContext, Protected, SurfaceOrigin};
 use skia_safe::gpu::gl::FramebufferInfo;
-use skia_safe::{ColorType, Surface};
+use skia_safe::color_space::{named_primaries, named_transfer_fn};
+use skia_safe::{AlphaType, BlendMode, ColorSpace, ColorType, ImageInfo, Paint, SamplingOptions, Surface};
 use windows::Win32::Graphics::OpenGL::{glGetIntegerv,};
 use crate::core::OverlayError;
 
 const GL_FRAMEBUFFERBINDING: u32 = 0x8CA6;
 
+/// Pixel format the overlay draws in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SurfaceColorType {
+    /// 8 bits per channel, drawn straight into the window
+    #[default]
+    Rgba8888,
+    /// 8 bits per channel in BGR order
+    Bgra8888,
+    /// 16-bit float per channel, blending and gradients are computed without banding.
+    /// The window still shows 8 bits per channel, this is drawing precision and not HDR output.
+    RgbaF16,
+    /// 10 bits per color channel and 2 for alpha, drawing precision only like `RgbaF16`
+    Rgba1010102,
+}
+
+impl SurfaceColorType {
+    fn color_type(self) -> ColorType {
+        match self {
+            SurfaceColorType::Rgba8888 => ColorType::RGBA8888,
+            SurfaceColorType::Bgra8888 => ColorType::BGRA8888,
+            SurfaceColorType::RgbaF16 => ColorType::RGBAF16,
+            SurfaceColorType::Rgba1010102 => ColorType::RGBA1010102,
+        }
+    }
+}
+
+/// Color space draw colors are converted into before they reach the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SurfaceColorSpace {
+    /// No conversion, colors are written as given like before color management existed
+    #[default]
+    Unmanaged,
+    Srgb,
+    /// The wide gamut of most HDR and recent laptop displays
+    DisplayP3,
+    /// sRGB primaries with linear blending, only precise enough with `SurfaceColorType::RgbaF16`
+    LinearSrgb,
+}
+
+impl SurfaceColorSpace {
+    fn color_space(self) -> Option<ColorSpace> {
+        match self {
+            SurfaceColorSpace::Unmanaged => None,
+            SurfaceColorSpace::Srgb => Some(ColorSpace::new_srgb()),
+            SurfaceColorSpace::DisplayP3 => ColorSpace::new_cicp(
+                named_primaries::CicpId::SMPTE_EG_432_1,
+                named_transfer_fn::CicpId::IEC61966_2_1
+            ),
+            SurfaceColorSpace::LinearSrgb => Some(ColorSpace::new_srgb_linear()),
+        }
+    }
+}
+
+/// How the renderer stores pixels. Other color types than `Rgba8888`, and linear sRGB, draw into an
+/// offscreen surface that is converted into the window's 8-bit framebuffer at the end of each frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SurfaceConfig {
+    pub color_type: SurfaceColorType,
+    pub color_space: SurfaceColorSpace,
+    /// Fail with `UnsupportedSurfaceConfig` instead of converting down when the window can't show the color type
+    pub require_native_output: bool,
+}
+
+impl SurfaceConfig {
+    pub fn new(color_type: SurfaceColorType, color_space: SurfaceColorSpace) -> Self {
+        Self { color_type, color_space, require_native_output: false }
+    }
+
+    /// Asks for real 10-bit or F16 output, which the window's framebuffer doesn't provide
+    pub fn with_native_output(mut self) -> Self {
+        self.require_native_output = true;
+        self
+    }
+
+    /// Checks the combination makes sense before anything is created
+    pub fn validate(&self) -> Result<(), OverlayError> {
+        if self.color_space == SurfaceColorSpace::LinearSrgb && self.color_type != SurfaceColorType::RgbaF16 {
+            return Err(OverlayError::UnsupportedSurfaceConfig(format!(
+                "{:?} can't store linear sRGB without visible banding, use RgbaF16",
+                self.color_type
+            )));
+        }
+
+        let high_precision = matches!(self.color_type, SurfaceColorType::RgbaF16 | SurfaceColorType::Rgba1010102);
+        if self.require_native_output && high_precision {
+            return Err(OverlayError::UnsupportedSurfaceConfig(format!(
+                "the window framebuffer is 8 bits per channel, {:?} is only available as drawing precision",
+                self.color_type
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn needs_offscreen(&self) -> bool {
+        self.color_type != SurfaceColorType::Rgba8888 || self.color_space == SurfaceColorSpace::LinearSrgb
+    }
+}
+
 #[derive(Clone)]
 pub struct SkiaContext {
     pub(crate) gr_context: DirectContext,
     pub(crate) surface: Surface,
+    /// Surface in the configured format when the window's can't be drawn into directly
+    offscreen: Option<Surface>,
+    config: SurfaceConfig,
 }
 
 impl SkiaContext {
     pub fn new(
         width: i32,
-        height: i32
+        height: i32,
+        config: SurfaceConfig
     ) -> Result<Self, OverlayError> {
+        config.validate()?;
+
         let interface = skia_safe::gpu::gl::Interface::new_native()
             .ok_or_else(|| {
                 println!("Failed to create native Skia interface");
@@ -34,6 +139,20 @@ impl SkiaContext {
 
         println!("Created DirectContext: {:?}", gr_context);
 
+        let color_type = config.color_type.color_type();
+        if !gr_context.color_type_supported_as_surface(color_type) {
+            log::warn!("surface color type {:?} is not supported by the GPU backend", config.color_type);
+            return Err(OverlayError::UnsupportedSurfaceColorType(config.color_type));
+        }
+
+        let color_space = config.color_space.color_space();
+        if config.color_space != SurfaceColorSpace::Unmanaged && color_space.is_none() {
+            return Err(OverlayError::UnsupportedSurfaceConfig(format!(
+                "color space {:?} could not be created",
+                config.color_space
+            )));
+        }
+
         let mut fboid: i32 = 0;
         unsafe {
             glGetIntegerv(GL_FRAMEBUFFERBINDING, &mut fboid);
@@ -55,12 +174,18 @@ impl SkiaContext {
 
         println!("Created BackendRenderTarget: {:?}", backend_render_target);
 
+        // The window is always 8-bit, it only takes on the color space when it can store it
+        let window_color_space = match config.color_space {
+            SurfaceColorSpace::LinearSrgb => Some(ColorSpace::new_srgb()),
+            _ => color_space.clone(),
+        };
+
         let surface = surfaces::wrap_backend_render_target(
             &mut gr_context,
             &backend_render_target,
             SurfaceOrigin::BottomLeft, // PLAY WITH THIS (TopLeft might be better for this overlay)
             ColorType::RGBA8888,
-            None,
+            window_color_space,
             None,
         ).ok_or_else(|| {
             println!("Failed to create Skia surface");
@@ -69,13 +194,89 @@ impl SkiaContext {
 
         println!("Created Skia Surface: {:?}", surface);
 
+        let offscreen = if config.needs_offscreen() {
+            let image_info = ImageInfo::new((width, height), color_type, AlphaType::Premul, color_space);
+            let offscreen = surfaces::render_target(
+                &mut gr_context,
+                Budgeted::Yes,
+                &image_info,
+                None,
+                SurfaceOrigin::BottomLeft,
+                None,
+                false,
+                None
+            ).ok_or_else(|| {
+                log::warn!("failed to create {:?} offscreen surface", config);
+                OverlayError::FailedToCreateSkiaSurface
+            })?;
+
+            log::debug!("created offscreen Skia surface: {:?}", offscreen);
+            Some(offscreen)
+        } else {
+            None
+        };
+
         Ok(Self {
             gr_context,
             surface,
+            offscreen,
+            config,
         })
     }
 
+    /// Canvas frames are drawn on, the offscreen surface's when there is one
     pub fn canvas(&mut self) -> &skia_safe::Canvas {
-        self.surface.canvas()
+        match &mut self.offscreen {
+            Some(offscreen) => offscreen.canvas(),
+            None => self.surface.canvas(),
+        }
     }
-}
\ No newline at end of file
+
+    pub fn config(&self) -> SurfaceConfig {
+        self.config
+    }
+
+    /// Copies the offscreen surface into the window, converting it to 8-bit and the window's color space
+    pub(crate) fn present(&mut self) {
+        if let Some(offscreen) = &mut self.offscreen {
+            let mut paint = Paint::default();
+            paint.set_blend_mode(BlendMode::Src);
+            // Hides banding from dropping to 8 bits per channel
+            paint.set_dither(true);
+
+            offscreen.draw(self.surface.canvas(), (0.0, 0.0), SamplingOptions::default(), Some(&paint));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let valid = [
+            SurfaceConfig::default(),
+            SurfaceConfig::new(SurfaceColorType::RgbaF16, SurfaceColorSpace::LinearSrgb),
+            SurfaceConfig::new(SurfaceColorType::Rgba1010102, SurfaceColorSpace::DisplayP3),
+            SurfaceConfig::new(SurfaceColorType::Bgra8888, SurfaceColorSpace::Srgb).with_native_output(),
+        ];
+        for config in valid {
+            assert!(config.validate().is_ok(), "{:?} should be valid", config);
+        }
+
+        let invalid = [
+            SurfaceConfig::new(SurfaceColorType::Rgba8888, SurfaceColorSpace::LinearSrgb),
+            SurfaceConfig::new(SurfaceColorType::Rgba1010102, SurfaceColorSpace::LinearSrgb),
+            SurfaceConfig::new(SurfaceColorType::RgbaF16, SurfaceColorSpace::Srgb).with_native_output(),
+            SurfaceConfig::new(SurfaceColorType::Rgba1010102, SurfaceColorSpace::Unmanaged).with_native_output(),
+        ];
+        for config in invalid {
+            assert!(
+                matches!(config.validate(), Err(OverlayError::UnsupportedSurfaceConfig(_))),
+                "{:?} should be rejected",
+                config
+            );
+        }
+    }
+}
@@ -0,0 +1,527 @@
+use skia_safe::{Font, Paint, Point, Rect, TextBlob, Typeface};
+use crate::core::{
+    Brush, Color, FontFeature, FontHandle, FontVariation, Overlay,
+    font::{FontRegistry, FontRun},
+    helper::to_color_4f,
+    shaping::{needs_shaper, shape_complex, with_variations, TextShaping},
+    text_cache::{ShapeKey, ShapedText}
+};
+
+/// A true stroke drawn around every glyph, underneath the fill
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextOutline {
+    /// Visible thickness outside the glyph edge
+    pub width: f32,
+    pub color: Color,
+}
+
+/// A copy of the text drawn behind it, offset and optionally blurred
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextShadow {
+    pub offset: (f32, f32),
+    /// Blur sigma in pixels, 0 gives a hard shadow
+    pub blur: f32,
+    pub color: Color,
+}
+
+/// Lines drawn along the text, thickness and position come from the font's metrics
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextDecoration {
+    pub underline: bool,
+    pub strikethrough: bool,
+    pub overline: bool,
+    /// Line color, the text's color or brush when unset
+    pub color: Option<Color>,
+}
+
+impl TextDecoration {
+    pub fn is_empty(&self) -> bool {
+        !self.underline && !self.strikethrough && !self.overline
+    }
+}
+
+/// A box filled behind the text, covering its advance and font height plus padding
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextBackground {
+    pub brush: Brush,
+    /// Horizontal and vertical space between the text and the edge of the box
+    pub padding: (f32, f32),
+    /// 0 gives square corners
+    pub corner_radius: f32,
+}
+
+/// Which part of the text lines up with the x coordinate it's drawn at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HorizontalAnchor {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// Which part of the text lines up with the y coordinate it's drawn at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VerticalAnchor {
+    /// Top of the font's ascent
+    Top,
+    /// Halfway between the ascent and descent
+    Middle,
+    #[default]
+    Baseline,
+    /// Bottom of the font's descent
+    Bottom,
+}
+
+/// Where the draw position sits relative to the text. Defaults to the left end of the baseline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextAnchor {
+    pub horizontal: HorizontalAnchor,
+    pub vertical: VerticalAnchor,
+}
+
+impl TextAnchor {
+    pub const TOP_LEFT: TextAnchor = TextAnchor::new(HorizontalAnchor::Left, VerticalAnchor::Top);
+    pub const CENTER: TextAnchor = TextAnchor::new(HorizontalAnchor::Center, VerticalAnchor::Middle);
+    pub const BASELINE_LEFT: TextAnchor = TextAnchor::new(HorizontalAnchor::Left, VerticalAnchor::Baseline);
+
+    pub const fn new(horizontal: HorizontalAnchor, vertical: VerticalAnchor) -> Self {
+        Self { horizontal, vertical }
+    }
+
+    /// Converts an anchored position into the left baseline origin text is drawn from
+    pub fn baseline_origin(&self, (x, y): (f32, f32), metrics: &TextMetrics) -> (f32, f32) {
+        let x = match self.horizontal {
+            HorizontalAnchor::Left => x,
+            HorizontalAnchor::Center => x - metrics.width / 2.0,
+            HorizontalAnchor::Right => x - metrics.width,
+        };
+
+        let y = match self.vertical {
+            VerticalAnchor::Top => y + metrics.ascent,
+            VerticalAnchor::Middle => y + (metrics.ascent - metrics.descent) / 2.0,
+            VerticalAnchor::Baseline => y,
+            VerticalAnchor::Bottom => y - metrics.descent,
+        };
+
+        (x, y)
+    }
+}
+
+/// How a run of text should look. Every text function accepts anything convertible into this,
+/// so a bare `FontHandle` works wherever only the font matters.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextStyle {
+    pub font: FontHandle,
+    pub color: Color,
+    /// Fills the glyphs instead of `color`, gradients span the text's ink bounds
+    pub brush: Option<Brush>,
+    pub outline: Option<TextOutline>,
+    pub shadow: Option<TextShadow>,
+    pub decoration: TextDecoration,
+    pub background: Option<TextBackground>,
+    pub anchor: TextAnchor,
+    pub shaping: TextShaping,
+    /// OpenType features, these make the text go through HarfBuzz even with `TextShaping::Simple`
+    pub features: Vec<FontFeature>,
+    /// Variable font axis positions, ignored by fonts that don't have the axis
+    pub variations: Vec<FontVariation>,
+}
+
+impl TextStyle {
+    pub fn new(font: FontHandle, color: impl Into<Color>) -> Self {
+        Self { font, color: color.into(), ..Default::default() }
+    }
+
+    pub fn with_brush(mut self, brush: impl Into<Brush>) -> Self {
+        self.brush = Some(brush.into());
+        self
+    }
+
+    pub fn with_outline(mut self, width: f32, color: impl Into<Color>) -> Self {
+        self.outline = Some(TextOutline { width, color: color.into() });
+        self
+    }
+
+    pub fn with_shadow(mut self, offset: (f32, f32), blur: f32, color: impl Into<Color>) -> Self {
+        self.shadow = Some(TextShadow { offset, blur, color: color.into() });
+        self
+    }
+
+    pub fn with_underline(mut self) -> Self {
+        self.decoration.underline = true;
+        self
+    }
+
+    pub fn with_strikethrough(mut self) -> Self {
+        self.decoration.strikethrough = true;
+        self
+    }
+
+    pub fn with_overline(mut self) -> Self {
+        self.decoration.overline = true;
+        self
+    }
+
+    pub fn with_decoration_color(mut self, color: impl Into<Color>) -> Self {
+        self.decoration.color = Some(color.into());
+        self
+    }
+
+    pub fn with_background(mut self, brush: impl Into<Brush>, padding: (f32, f32), corner_radius: f32) -> Self {
+        self.background = Some(TextBackground { brush: brush.into(), padding, corner_radius });
+        self
+    }
+
+    pub fn with_anchor(mut self, anchor: TextAnchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    pub fn with_shaping(mut self, shaping: TextShaping) -> Self {
+        self.shaping = shaping;
+        self
+    }
+
+    pub fn with_feature(mut self, feature: FontFeature) -> Self {
+        self.features.retain(|existing| existing.tag != feature.tag);
+        self.features.push(feature);
+        self
+    }
+
+    /// Digits with equal advances, for counters and timers that update every frame
+    pub fn with_tabular_numbers(self) -> Self {
+        self.with_feature(FontFeature::TABULAR_NUMBERS)
+    }
+
+    pub fn with_variation(mut self, variation: FontVariation) -> Self {
+        self.variations.retain(|existing| existing.axis != variation.axis);
+        self.variations.push(variation);
+        self
+    }
+
+    /// Glyph fill paint, gradients span `bounds`
+    pub(crate) fn fill_paint(&self, bounds: Rect) -> Paint {
+        match &self.brush {
+            Some(brush) => brush.to_paint(bounds),
+            None => Brush::Solid(self.color).to_paint(bounds),
+        }
+    }
+
+    /// Stroke around the glyphs, doubled because it's centered on the glyph edge and the fill covers the inner half
+    pub(crate) fn outline_paint(&self) -> Option<Paint> {
+        let outline = self.outline?;
+
+        let mut paint = Paint::new(to_color_4f(outline.color), None);
+        paint.set_anti_alias(true);
+        paint.set_style(skia_safe::paint::Style::Stroke);
+        paint.set_stroke_width(outline.width * 2.0);
+        paint.set_stroke_join(skia_safe::paint::Join::Round);
+        Some(paint)
+    }
+
+    /// The background box of a line drawn with its left baseline at `origin`
+    pub(crate) fn background_rect(&self, metrics: &TextMetrics, origin: (f32, f32)) -> Option<Rect> {
+        self.background.as_ref()
+            .map(|background| metrics.layout_bounds(origin).with_outset(background.padding))
+    }
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self {
+            font: FontHandle::DEFAULT,
+            color: Color::WHITE,
+            brush: None,
+            outline: None,
+            shadow: None,
+            decoration: TextDecoration::default(),
+            background: None,
+            anchor: TextAnchor::default(),
+            shaping: TextShaping::default(),
+            features: Vec::new(),
+            variations: Vec::new(),
+        }
+    }
+}
+
+impl From<FontHandle> for TextStyle {
+    fn from(font: FontHandle) -> Self {
+        Self { font, ..Default::default() }
+    }
+}
+
+impl From<&TextStyle> for TextStyle {
+    fn from(style: &TextStyle) -> Self {
+        style.clone()
+    }
+}
+
+/// Measurements for a run of text, all in pixels relative to the baseline origin
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextMetrics {
+    /// Horizontal advance of the whole run (where the next glyph would start)
+    pub width: f32,
+    /// Tight ink bounds of the glyphs, relative to the baseline origin
+    pub bounds: Rect,
+    /// Distance from the baseline to the top of the font (positive)
+    pub ascent: f32,
+    /// Distance from the baseline to the bottom of the font (positive)
+    pub descent: f32,
+    /// Recommended extra space between lines
+    pub leading: f32,
+    /// Baseline to baseline distance (ascent + descent + leading)
+    pub line_height: f32,
+}
+
+impl TextMetrics {
+    /// Height of a single line without leading
+    pub fn height(&self) -> f32 {
+        self.ascent + self.descent
+    }
+
+    /// Advance by ascent+descent box of the line drawn with its left baseline at `origin`
+    pub fn layout_bounds(&self, (x, y): (f32, f32)) -> Rect {
+        Rect::new(x, y - self.ascent, x + self.width, y + self.descent)
+    }
+}
+
+/// Measures pre-resolved font runs laid out one after another on the same baseline
+fn measure_runs(primary: &Font, text: &str, runs: &[FontRun]) -> TextMetrics {
+    let (line_height, metrics) = primary.metrics();
+
+    let mut result = TextMetrics {
+        width: 0.0,
+        bounds: Rect::new_empty(),
+        ascent: -metrics.ascent,
+        descent: metrics.descent,
+        leading: metrics.leading,
+        line_height,
+    };
+
+    for run in runs {
+        let (advance, bounds) = run.font.measure_str(&text[run.range.clone()], None);
+        result.bounds.join(bounds.with_offset((result.width, 0.0)));
+        result.width += advance;
+
+        // Fallback fonts can be taller than the primary font, the line has to fit them
+        if !Typeface::equal(run.font.typeface(), primary.typeface()) {
+            let (run_line_height, run_metrics) = run.font.metrics();
+            result.ascent = result.ascent.max(-run_metrics.ascent);
+            result.descent = result.descent.max(run_metrics.descent);
+            result.line_height = result.line_height.max(run_line_height);
+        }
+    }
+
+    result
+}
+
+/// Rects of the decoration lines for a run `width` wide, relative to its left baseline origin.
+/// Fonts that don't specify thickness or position get values derived from their size.
+pub(crate) fn decoration_rects(font: &Font, decoration: &TextDecoration, width: f32) -> Vec<Rect> {
+    let (_, metrics) = font.metrics();
+    let fallback_thickness = (font.size() / 18.0).max(1.0);
+    let mut rects = Vec::new();
+
+    // Skia gives the underline's top edge and the strikeout's bottom edge, both relative to the baseline
+    if decoration.underline {
+        let thickness = metrics.underline_thickness().unwrap_or(fallback_thickness);
+        let top = metrics.underline_position().unwrap_or(thickness);
+        rects.push(Rect::new(0.0, top, width, top + thickness));
+    }
+
+    if decoration.strikethrough {
+        let thickness = metrics.strikeout_thickness().unwrap_or(fallback_thickness);
+        let bottom = metrics.strikeout_position().unwrap_or_else(|| {
+            if metrics.x_height > 0.0 { -metrics.x_height / 2.0 } else { metrics.ascent * 0.3 }
+        });
+        rects.push(Rect::new(0.0, bottom - thickness, width, bottom));
+    }
+
+    if decoration.overline {
+        let thickness = metrics.underline_thickness().unwrap_or(fallback_thickness);
+        rects.push(Rect::new(0.0, metrics.ascent, width, metrics.ascent + thickness));
+    }
+
+    rects
+}
+
+/// Turns a single line into text blobs positioned relative to the left baseline, using the key's shaping mode
+pub(crate) fn shape_text(fonts: &FontRegistry, key: &ShapeKey) -> ShapedText {
+    shape_text_with(fonts, fonts.get(key.font), key)
+}
+
+/// Same as `shape_text` with a font that doesn't have to be registered, like a resized copy
+pub(crate) fn shape_text_with(fonts: &FontRegistry, font: &Font, key: &ShapeKey) -> ShapedText {
+    let primary = with_variations(font, &key.variations);
+    if needs_shaper(key) {
+        return shape_complex(fonts, &primary, key);
+    }
+
+    // Each run uses the first font that has glyphs for it. Color emoji fonts ignore the paint color.
+    let text = key.text.as_str();
+    let runs = fonts.font_runs(&primary, text);
+    let metrics = measure_runs(&primary, text, &runs);
+
+    let mut blobs = Vec::new();
+    let mut advance = 0.0;
+    for run in runs {
+        let run_text = &text[run.range];
+        if let Some(text_blob) = TextBlob::new(run_text, &run.font) {
+            blobs.push((text_blob, Point::new(advance, 0.0)));
+        }
+        advance += run.font.measure_str(run_text, None).0;
+    }
+
+    ShapedText { blobs, metrics }
+}
+
+/// Grows a shaped line's bounds to cover the style's outline, decorations and background
+fn styled_bounds(style: &TextStyle, font: &Font, mut metrics: TextMetrics) -> TextMetrics {
+    if let Some(outline) = style.outline {
+        if !metrics.bounds.is_empty() {
+            metrics.bounds = metrics.bounds.with_outset((outline.width, outline.width));
+        }
+    }
+
+    if !style.decoration.is_empty() {
+        for rect in decoration_rects(font, &style.decoration, metrics.width) {
+            metrics.bounds.join(rect);
+        }
+    }
+
+    if let Some(background) = style.background_rect(&metrics, (0.0, 0.0)) {
+        metrics.bounds.join(background);
+    }
+
+    metrics
+}
+
+/// Measures text that may contain embedded newlines, one line at a time with `measure_line`.
+/// Width is the widest line, bounds are the union of every line's ink bounds.
+pub(crate) fn measure_lines(text: &str, measure_line: impl Fn(&str) -> TextMetrics) -> TextMetrics {
+    let mut lines = text.split('\n').map(|line| line.trim_end_matches('\r'));
+
+    // `split` always yields at least one item
+    let mut result = measure_line(lines.next().unwrap_or_default());
+
+    for (index, line) in lines.enumerate() {
+        let line_metrics = measure_line(line);
+        let offset_y = (index + 1) as f32 * result.line_height;
+
+        result.width = result.width.max(line_metrics.width);
+
+        // Joining ignores empty rects, so blank lines don't stretch the bounds
+        result.bounds.join(line_metrics.bounds.with_offset((0.0, offset_y)));
+    }
+
+    result
+}
+
+impl Overlay {
+    // MEASUREMENT FUNCTIONS ------------------
+
+    /// Shapes a single line of text, going through the shaped text cache
+    pub(crate) fn shaped_text(&self, style: &TextStyle, text: &str) -> ShapedText {
+        self.text_cache().get_or_shape(ShapeKey::new(text, style), |key| shape_text(&self.fonts, key))
+    }
+
+    /// Measures a single line of text with the default font
+    pub fn measure_text(&self, text: impl AsRef<str>) -> TextMetrics {
+        self.measure_text_with(text, FontHandle::DEFAULT)
+    }
+
+    /// Metrics of a shaped line with the bounds grown to cover the outline, decorations and background
+    fn styled_metrics(&self, style: &TextStyle, text: &str) -> TextMetrics {
+        let metrics = self.shaped_text(style, text).metrics;
+        styled_bounds(style, self.fonts.get(style.font), metrics)
+    }
+
+    /// Same as `measure_text_with` but drawn with `font` instead of the style's font.
+    /// Skips the shaped text cache, so trying out sizes doesn't register fonts or fill the cache.
+    pub(crate) fn measure_text_with_font(&self, text: &str, style: &TextStyle, font: &Font) -> TextMetrics {
+        let metrics = shape_text_with(&self.fonts, font, &ShapeKey::new(text, style)).metrics;
+        styled_bounds(style, font, metrics)
+    }
+
+    /// Measures a single line of text with the font of the given style.
+    /// Bounds include the outline, decorations and background box.
+    pub fn measure_text_with(&self, text: impl AsRef<str>, style: impl Into<TextStyle>) -> TextMetrics {
+        self.styled_metrics(&style.into(), text.as_ref())
+    }
+
+    /// Measures text with embedded newlines, each line advancing by the font's line height
+    pub fn measure_multiline_text(&self, text: impl AsRef<str>) -> TextMetrics {
+        self.measure_multiline_text_with(text, FontHandle::DEFAULT)
+    }
+
+    pub fn measure_multiline_text_with(
+        &self,
+        text: impl AsRef<str>,
+        style: impl Into<TextStyle>
+    ) -> TextMetrics {
+        let style = style.into();
+        measure_lines(text.as_ref(), |line| self.styled_metrics(&style, line))
+    }
+
+    /// Total height taken by text with embedded newlines
+    pub fn get_multiline_text_height(&self, text: impl AsRef<str>, style: impl Into<TextStyle>) -> f32 {
+        let text = text.as_ref();
+        let metrics = self.measure_multiline_text_with(text, style);
+        let line_count = text.split('\n').count() as f32;
+
+        metrics.height() + (line_count - 1.0) * metrics.line_height
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(width: f32, bounds: Rect) -> TextMetrics {
+        TextMetrics { width, bounds, ascent: 8.0, descent: 2.0, leading: 2.0, line_height: 12.0 }
+    }
+
+    #[test]
+    fn test_metrics() {
+        let metrics = line(30.0, Rect::new(1.0, -7.0, 29.0, 2.0));
+        assert_eq!(metrics.height(), 10.0);
+        assert_eq!(metrics.layout_bounds((10.0, 20.0)), Rect::new(10.0, 12.0, 40.0, 22.0));
+    }
+
+    #[test]
+    fn test_measure_lines() {
+        // One unit of width per character, blank lines have no ink
+        let measure = |text: &str| {
+            let width = text.chars().count() as f32;
+            let bounds = if text.is_empty() { Rect::new_empty() } else { Rect::new(0.0, -7.0, width, 2.0) };
+            line(width, bounds)
+        };
+
+        let single = measure_lines("abc", measure);
+        assert_eq!(single, measure("abc"));
+
+        let metrics = measure_lines("ab\r\nabcd\n\nx", measure);
+        assert_eq!(metrics.width, 4.0);
+        assert_eq!(metrics.line_height, 12.0);
+        assert_eq!(metrics.bounds, Rect::new(0.0, -7.0, 4.0, 38.0));
+    }
+
+    #[test]
+    fn test_baseline_origin() {
+        let metrics = line(30.0, Rect::new_empty());
+        let origin = |horizontal, vertical| TextAnchor::new(horizontal, vertical).baseline_origin((100.0, 50.0), &metrics);
+
+        assert_eq!(TextAnchor::default().baseline_origin((100.0, 50.0), &metrics), (100.0, 50.0));
+        assert_eq!(TextAnchor::TOP_LEFT.baseline_origin((100.0, 50.0), &metrics), (100.0, 58.0));
+        assert_eq!(TextAnchor::CENTER.baseline_origin((100.0, 50.0), &metrics), (85.0, 53.0));
+        assert_eq!(origin(HorizontalAnchor::Right, VerticalAnchor::Bottom), (70.0, 48.0));
+    }
+}
@@ -0,0 +1,264 @@
+use std::num::NonZeroUsize;
+use std::sync::{MutexGuard, PoisonError};
+use lru::LruCache;
+use skia_safe::{Point, TextBlob};
+use crate::core::{FontFeature, FontHandle, FontVariation, Overlay, TextMetrics, TextShaping, TextStyle};
+
+const DEFAULT_CAPACITY: usize = 512;
+
+/// Everything that changes how a string is shaped. Color, outline and anchor don't, so they aren't part of it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ShapeKey {
+    pub text: String,
+    pub font: FontHandle,
+    pub shaping: TextShaping,
+    pub features: Vec<FontFeature>,
+    pub variations: Vec<FontVariation>,
+}
+
+impl ShapeKey {
+    pub fn new(text: &str, style: &TextStyle) -> Self {
+        Self {
+            text: text.to_string(),
+            font: style.font,
+            shaping: style.shaping,
+            features: style.features.clone(),
+            variations: style.variations.clone(),
+        }
+    }
+}
+
+/// A string turned into positioned text blobs, one per fallback font run, plus its measurements.
+/// Blob origins are relative to the left baseline of the text.
+#[derive(Debug, Clone)]
+pub(crate) struct ShapedText {
+    pub blobs: Vec<(TextBlob, Point)>,
+    pub metrics: TextMetrics,
+}
+
+/// Hit/miss counters for the shaped text cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TextCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub len: usize,
+    pub capacity: usize,
+}
+
+impl TextCacheStats {
+    /// Fraction of lookups that were served from the cache
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 { 0.0 } else { self.hits as f64 / total as f64 }
+    }
+}
+
+/// Least recently used cache of shaped strings. A capacity of 0 disables caching.
+pub(crate) struct TextCache {
+    entries: Option<LruCache<ShapeKey, ShapedText>>,
+    hits: u64,
+    misses: u64,
+}
+
+impl TextCache {
+    pub fn new() -> Self {
+        let mut cache = Self { entries: None, hits: 0, misses: 0 };
+        cache.set_capacity(DEFAULT_CAPACITY);
+        cache
+    }
+
+    /// Returns the cached shape for a key, or shapes it with `shape` and remembers the result
+    pub fn get_or_shape(&mut self, key: ShapeKey, shape: impl FnOnce(&ShapeKey) -> ShapedText) -> ShapedText {
+        let Some(entries) = self.entries.as_mut() else {
+            self.misses += 1;
+            return shape(&key);
+        };
+
+        if let Some(shaped) = entries.get(&key) {
+            self.hits += 1;
+            return shaped.clone();
+        }
+
+        self.misses += 1;
+        let shaped = shape(&key);
+        entries.put(key, shaped.clone());
+        shaped
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        match (NonZeroUsize::new(capacity), self.entries.as_mut()) {
+            (None, _) => self.entries = None,
+            (Some(capacity), Some(entries)) => entries.resize(capacity),
+            (Some(capacity), None) => self.entries = Some(LruCache::new(capacity)),
+        }
+    }
+
+    /// Drops every entry shaped with the given font
+    pub fn invalidate_font(&mut self, font: FontHandle) {
+        if let Some(entries) = self.entries.as_mut() {
+            let stale: Vec<ShapeKey> = entries.iter()
+                .filter(|(key, _)| key.font == font)
+                .map(|(key, _)| key.clone())
+                .collect();
+
+            for key in stale {
+                entries.pop(&key);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        if let Some(entries) = self.entries.as_mut() {
+            entries.clear();
+        }
+    }
+
+    pub fn stats(&self) -> TextCacheStats {
+        TextCacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            len: self.entries.as_ref().map_or(0, |entries| entries.len()),
+            capacity: self.entries.as_ref().map_or(0, |entries| entries.cap().get()),
+        }
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.hits = 0;
+        self.misses = 0;
+    }
+}
+
+impl Overlay {
+    // TEXT CACHE FUNCTIONS -------------------
+
+    /// Hit/miss statistics of the shaped text cache
+    pub fn text_cache_stats(&self) -> TextCacheStats {
+        self.text_cache().stats()
+    }
+
+    pub fn reset_text_cache_stats(&self) {
+        self.text_cache().reset_stats();
+    }
+
+    /// Sets how many shaped strings are kept, evicting the least recently used. 0 disables the cache.
+    pub fn set_text_cache_capacity(&self, capacity: usize) {
+        self.text_cache().set_capacity(capacity);
+    }
+
+    /// Drops every cached string
+    pub fn clear_text_cache(&self) {
+        self.text_cache().clear();
+    }
+
+    /// Drops cached strings shaped with one font, call this after changing or re-registering it
+    pub fn invalidate_text_cache(&self, font: FontHandle) {
+        self.text_cache().invalidate_font(font);
+    }
+
+    pub(crate) fn text_cache(&self) -> MutexGuard<'_, TextCache> {
+        self.text_cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use skia_safe::Rect;
+    use crate::core::Color;
+    use super::*;
+
+    fn key(text: &str, font: usize) -> ShapeKey {
+        ShapeKey::new(text, &TextStyle::new(FontHandle(font), Color::WHITE))
+    }
+
+    fn shaped(width: f32) -> ShapedText {
+        ShapedText {
+            blobs: Vec::new(),
+            metrics: TextMetrics {
+                width,
+                bounds: Rect::new_empty(),
+                ascent: 0.0,
+                descent: 0.0,
+                leading: 0.0,
+                line_height: 0.0,
+            },
+        }
+    }
+
+    /// Width of the cached entry, or `None` if the key had to be shaped again
+    fn cached_width(cache: &mut TextCache, key: ShapeKey) -> Option<f32> {
+        let mut shaped_again = false;
+        let result = cache.get_or_shape(key, |_| {
+            shaped_again = true;
+            shaped(-1.0)
+        });
+        (!shaped_again).then_some(result.metrics.width)
+    }
+
+    #[test]
+    fn test_hits_and_misses() {
+        let mut cache = TextCache::new();
+        assert_eq!(cache.get_or_shape(key("a", 0), |_| shaped(1.0)).metrics.width, 1.0);
+        assert_eq!(cache.get_or_shape(key("a", 0), |_| shaped(2.0)).metrics.width, 1.0);
+        cache.get_or_shape(key("b", 0), |_| shaped(3.0));
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.len), (1, 2, 2));
+        assert_eq!(stats.capacity, DEFAULT_CAPACITY);
+        assert!((stats.hit_rate() - 1.0 / 3.0).abs() < 1e-9);
+
+        cache.reset_stats();
+        assert_eq!((cache.stats().hits, cache.stats().misses, cache.stats().len), (0, 0, 2));
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut cache = TextCache::new();
+        cache.set_capacity(2);
+        cache.get_or_shape(key("a", 0), |_| shaped(1.0));
+        cache.get_or_shape(key("b", 0), |_| shaped(2.0));
+
+        // Using "a" again makes "b" the oldest entry
+        assert_eq!(cached_width(&mut cache, key("a", 0)), Some(1.0));
+        cache.get_or_shape(key("c", 0), |_| shaped(3.0));
+
+        assert_eq!(cached_width(&mut cache, key("b", 0)), None);
+        assert_eq!(cached_width(&mut cache, key("c", 0)), Some(3.0));
+        assert_eq!(cache.stats().len, 2);
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut cache = TextCache::new();
+        for (index, text) in ["a", "b", "c"].into_iter().enumerate() {
+            cache.get_or_shape(key(text, 0), |_| shaped(index as f32));
+        }
+
+        // Shrinking keeps the most recently used entries
+        cache.set_capacity(1);
+        assert_eq!(cache.stats().len, 1);
+        assert_eq!(cached_width(&mut cache, key("c", 0)), Some(2.0));
+
+        // A capacity of 0 shapes every call and remembers nothing
+        cache.set_capacity(0);
+        assert_eq!(cached_width(&mut cache, key("c", 0)), None);
+        assert_eq!(cached_width(&mut cache, key("c", 0)), None);
+        assert_eq!((cache.stats().len, cache.stats().capacity), (0, 0));
+
+        cache.set_capacity(4);
+        cache.get_or_shape(key("c", 0), |_| shaped(2.0));
+        assert_eq!(cached_width(&mut cache, key("c", 0)), Some(2.0));
+    }
+
+    #[test]
+    fn test_invalidate_font() {
+        let mut cache = TextCache::new();
+        cache.get_or_shape(key("a", 0), |_| shaped(1.0));
+        cache.get_or_shape(key("a", 1), |_| shaped(2.0));
+        cache.get_or_shape(key("b", 1), |_| shaped(3.0));
+
+        cache.invalidate_font(FontHandle(1));
+        assert_eq!(cache.stats().len, 1);
+        assert_eq!(cached_width(&mut cache, key("a", 0)), Some(1.0));
+        assert_eq!(cached_width(&mut cache, key("a", 1)), None);
+    }
+}
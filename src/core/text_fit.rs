@@ -0,0 +1,264 @@
+use crate::core::{Overlay, OverlayError, TextMetrics, TextStyle, font::is_cluster_continuation, geometry};
+
+const ELLIPSIS: &str = "\u{2026}";
+const COMBINING_KEYCAP: char = '\u{20E3}';
+
+/// Font sizes tried while shrinking are multiples of this
+const SIZE_STEP: f32 = 0.5;
+
+/// Which part of the string is replaced by the ellipsis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TruncateAt {
+    /// "…long name", keeps the end visible
+    Start,
+    /// "long…name", keeps both ends visible, good for paths and IDs
+    Middle,
+    /// "long na…"
+    #[default]
+    End,
+}
+
+/// Space text has to fit in and what may be done to make it fit.
+/// Shrinking is tried first, truncation only if the text is still too wide at the minimum size.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FitOptions {
+    pub max_width: f32,
+    /// Limits the ascent+descent height of the line, only shrinking helps with this
+    pub max_height: Option<f32>,
+    /// Smallest font size shrinking may go down to, rounded up to a multiple of 0.5. `None` keeps the style's size.
+    pub min_size: Option<f32>,
+    /// Where to cut the string, `None` never truncates
+    pub truncate: Option<TruncateAt>,
+}
+
+impl FitOptions {
+    pub fn new(max_width: f32) -> Self {
+        Self { max_width, ..Default::default() }
+    }
+
+    pub fn with_max_height(mut self, max_height: f32) -> Self {
+        self.max_height = Some(max_height);
+        self
+    }
+
+    pub fn with_shrink(mut self, min_size: f32) -> Self {
+        self.min_size = Some(min_size);
+        self
+    }
+
+    pub fn with_truncate(mut self, at: TruncateAt) -> Self {
+        self.truncate = Some(at);
+        self
+    }
+
+    fn fits(&self, metrics: &TextMetrics) -> bool {
+        metrics.width <= self.max_width && self.max_height.is_none_or(|max_height| metrics.height() <= max_height)
+    }
+}
+
+/// The string, size and style `Overlay::fit_text` settled on
+#[derive(Debug, Clone, PartialEq)]
+pub struct FittedText {
+    pub text: String,
+    /// Style to draw `text` with, its font is resized when the text was shrunk
+    pub style: TextStyle,
+    pub size: f32,
+    pub metrics: TextMetrics,
+    pub truncated: bool,
+    /// False when even the minimum size and an ellipsis alone were too big
+    pub fits: bool,
+}
+
+/// Splits text into clusters that must not be cut apart: a character with its combining marks,
+/// variation selectors, skin tones and keycap, emoji joined with ZWJ, and flags made of two regional indicators
+fn clusters(text: &str) -> Vec<&str> {
+    let mut clusters: Vec<&str> = Vec::new();
+    let mut start = 0;
+    let mut joined = false;
+    let mut half_flag = false;
+
+    for (index, ch) in text.char_indices() {
+        let completes_flag = half_flag && is_regional_indicator(ch);
+        let continues = joined || completes_flag || is_cluster_continuation(ch) || ch == COMBINING_KEYCAP;
+        if index > 0 && !continues {
+            clusters.push(&text[start..index]);
+            start = index;
+        }
+        joined = ch == '\u{200D}';
+        half_flag = is_regional_indicator(ch) && !completes_flag;
+    }
+
+    if start < text.len() {
+        clusters.push(&text[start..]);
+    }
+    clusters
+}
+
+/// Flags are written as two of these letters, like U+1F1E9 U+1F1EA for DE
+fn is_regional_indicator(ch: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&ch)
+}
+
+/// Smallest size shrinking goes down to, rounded up to a size step so every size it can settle on below
+/// the base size is a step. That keeps the font variants fitting registers to a small set per font.
+fn shrink_min_size(min_size: f32, base_size: f32) -> f32 {
+    ((min_size / SIZE_STEP).ceil() * SIZE_STEP).max(SIZE_STEP).min(base_size)
+}
+
+/// The string with all but `keep` of its clusters replaced by an ellipsis
+fn truncated(clusters: &[&str], keep: usize, at: TruncateAt) -> String {
+    let (head, tail) = match at {
+        TruncateAt::Start => (0, keep),
+        TruncateAt::Middle => (keep - keep / 2, keep / 2),
+        TruncateAt::End => (keep, 0),
+    };
+
+    let mut result = clusters[..head].concat();
+    result.push_str(ELLIPSIS);
+    result.push_str(&clusters[clusters.len() - tail..].concat());
+    result
+}
+
+impl Overlay {
+    // TEXT FIT FUNCTIONS ---------------------
+
+    /// Shrinks and/or truncates a line of text until it fits the given box.
+    /// Shrunk sizes are multiples of 0.5, and only the one settled on is registered as a font variant,
+    /// so repeated calls reuse the same few fonts.
+    pub fn fit_text(
+        &mut self,
+        text: impl AsRef<str>,
+        style: impl Into<TextStyle>,
+        options: &FitOptions
+    ) -> FittedText {
+        let text = text.as_ref();
+        let mut style = style.into();
+        let base_font = style.font;
+        let base_size = self.fonts.get(base_font).size();
+        let mut size = base_size;
+        let mut metrics = self.measure_text_with(text, &style);
+
+        let min_size = options.min_size.map(|min_size| shrink_min_size(min_size, base_size));
+        if let Some(min_size) = min_size.filter(|min_size| *min_size < base_size && !options.fits(&metrics)) {
+            // Widths scale almost linearly with the size, so start at the estimate and step down past hinting rounding
+            let width_scale = options.max_width / metrics.width;
+            let height_scale = options.max_height.map_or(f32::INFINITY, |max_height| max_height / metrics.height());
+            size = ((base_size * width_scale.min(height_scale) / SIZE_STEP).floor() * SIZE_STEP)
+                .clamp(min_size, base_size);
+
+            // Sizes are tried with throwaway fonts, registering each would grow the registry with every call
+            let base = self.fonts.get(base_font).clone();
+            loop {
+                let font = base.with_size(size).unwrap_or_else(|| base.clone());
+                metrics = self.measure_text_with_font(text, &style, &font);
+
+                if size <= min_size || options.fits(&metrics) {
+                    break;
+                }
+                size = (size - SIZE_STEP).max(min_size);
+            }
+
+            if size < base_size {
+                style.font = self.fonts.variant(base_font, false, false, Some(size));
+            }
+        }
+
+        let mut result = FittedText {
+            text: text.to_string(),
+            style,
+            size,
+            metrics,
+            truncated: false,
+            fits: options.fits(&metrics),
+        };
+
+        if let Some(at) = options.truncate.filter(|_| !result.fits) {
+            let clusters = clusters(text);
+
+            // Largest number of kept clusters that still fits
+            let (mut low, mut high) = (0, clusters.len().saturating_sub(1));
+            while low < high {
+                let keep = (low + high).div_ceil(2);
+                if options.fits(&self.measure_text_with(truncated(&clusters, keep, at), &result.style)) {
+                    low = keep;
+                } else {
+                    high = keep - 1;
+                }
+            }
+
+            let candidate = truncated(&clusters, low, at);
+            let metrics = self.measure_text_with(&candidate, &result.style);
+
+            result.fits = options.fits(&metrics);
+            result.text = if result.fits { candidate } else { String::new() };
+            result.metrics = if result.fits { metrics } else { self.measure_text_with("", &result.style) };
+            result.truncated = true;
+        }
+
+        result
+    }
+
+    /// Fits a line of text with `fit_text` and draws it positioned by the style's anchor.
+    /// Returns what was drawn and the advance by ascent+descent box it occupies.
+    pub fn draw_fitted_text(
+        &mut self,
+        pos: impl Into<geometry::Point>,
+        text: impl AsRef<str>,
+        style: impl Into<TextStyle>,
+        options: &FitOptions
+    ) -> Result<(FittedText, geometry::Rect), OverlayError> {
+        let fitted = self.fit_text(text, style, options);
+        let bounds = self.draw_text_with(pos, &fitted.text, &fitted.style)?;
+        Ok((fitted, bounds))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncated() {
+        let clusters = clusters("abcdefg");
+
+        assert_eq!(truncated(&clusters, 3, TruncateAt::End), "abc\u{2026}");
+        assert_eq!(truncated(&clusters, 3, TruncateAt::Start), "\u{2026}efg");
+        assert_eq!(truncated(&clusters, 3, TruncateAt::Middle), "ab\u{2026}g");
+        assert_eq!(truncated(&clusters, 0, TruncateAt::Middle), "\u{2026}");
+    }
+
+    #[test]
+    fn test_clusters() {
+        // e + combining acute, thumbs up + skin tone, man + ZWJ + laptop
+        let text = "ae\u{301}\u{1F44D}\u{1F3FD}\u{1F468}\u{200D}\u{1F4BB}b";
+
+        assert_eq!(
+            clusters(text),
+            ["a", "e\u{301}", "\u{1F44D}\u{1F3FD}", "\u{1F468}\u{200D}\u{1F4BB}", "b"]
+        );
+        assert_eq!(truncated(&clusters(text), 2, TruncateAt::End), "ae\u{301}\u{2026}");
+    }
+
+    #[test]
+    fn test_flags_and_keycaps() {
+        // Two flags back to back, keycap one, then a lone regional indicator
+        let text = "\u{1F1E9}\u{1F1EA}\u{1F1EB}\u{1F1F7}1\u{FE0F}\u{20E3}\u{1F1E6}x";
+
+        assert_eq!(
+            clusters(text),
+            ["\u{1F1E9}\u{1F1EA}", "\u{1F1EB}\u{1F1F7}", "1\u{FE0F}\u{20E3}", "\u{1F1E6}", "x"]
+        );
+        assert_eq!(truncated(&clusters(text), 1, TruncateAt::End), "\u{1F1E9}\u{1F1EA}\u{2026}");
+        assert_eq!(truncated(&clusters(text), 2, TruncateAt::Start), "\u{2026}\u{1F1E6}x");
+    }
+
+    #[test]
+    fn test_shrink_min_size() {
+        assert_eq!(shrink_min_size(8.0, 16.0), 8.0);
+        assert_eq!(shrink_min_size(7.3, 16.0), 7.5);
+        assert_eq!(shrink_min_size(0.0, 16.0), 0.5);
+        assert_eq!(shrink_min_size(15.9, 15.8), 15.8);
+    }
+}
@@ -0,0 +1,233 @@
+use skia_safe::{ContourMeasureIter, Path, Point, RSXform, Rect, TextBlob};
+use crate::core::{
+    Overlay, OverlayError, TextStyle,
+    geometry,
+    draw::draw_text_blobs,
+    shaping::glyph_runs,
+    text_cache::ShapeKey
+};
+
+/// Where the text sits along the path before `PathTextLayout::offset` is applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PathTextAlign {
+    /// Text starts at the beginning of the path
+    #[default]
+    Start,
+    /// Text is centered on the middle of the path
+    Center,
+    /// Text ends at the end of the path
+    End,
+}
+
+/// How each glyph is turned as it follows the path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GlyphOrientation {
+    /// Glyphs turn with the path's direction, like a curved banner
+    #[default]
+    Rotated,
+    /// Glyphs stay upright and only their position follows the path
+    Upright,
+}
+
+/// Placement of text drawn along a path
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PathTextLayout {
+    /// Extra distance along the path added after alignment, negative moves the text back
+    pub offset: f32,
+    pub align: PathTextAlign,
+    pub orientation: GlyphOrientation,
+}
+
+impl PathTextLayout {
+    pub fn new(align: PathTextAlign) -> Self {
+        Self { align, ..Default::default() }
+    }
+
+    pub fn with_offset(mut self, offset: f32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_orientation(mut self, orientation: GlyphOrientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    /// Distance along a path of `path_length` where text `text_width` wide starts
+    fn start_distance(&self, path_length: f32, text_width: f32) -> f32 {
+        let start = match self.align {
+            PathTextAlign::Start => 0.0,
+            PathTextAlign::Center => (path_length - text_width) / 2.0,
+            PathTextAlign::End => path_length - text_width,
+        };
+        start + self.offset
+    }
+}
+
+/// Rotation and origin of one glyph whose horizontal center sits on the path
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct GlyphPlacement {
+    cos: f32,
+    sin: f32,
+    origin: Point,
+}
+
+impl GlyphPlacement {
+    /// `baseline` is the distance from the path to the glyph's baseline along its down direction
+    fn new(point: Point, tangent: Point, orientation: GlyphOrientation, half_width: f32, baseline: f32) -> Self {
+        let (cos, sin) = match orientation {
+            GlyphOrientation::Rotated => (tangent.x, tangent.y),
+            GlyphOrientation::Upright => (1.0, 0.0),
+        };
+
+        let rotated = Self { cos, sin, origin: Point::default() };
+        Self { origin: point + rotated.map(Point::new(-half_width, baseline)), ..rotated }
+    }
+
+    /// Maps a point relative to the glyph origin onto the canvas
+    fn map(&self, p: Point) -> Point {
+        self.origin + Point::new(self.cos * p.x - self.sin * p.y, self.sin * p.x + self.cos * p.y)
+    }
+}
+
+impl Overlay {
+    // PATH TEXT FUNCTIONS --------------------
+
+    /// Draws a line of text following the first contour of `path`.
+    /// The style's vertical anchor decides how the text sits on the path, its horizontal anchor is replaced by `layout`.
+    /// Glyphs that fall off either end of the path are skipped. Returns the bounds of the drawn glyphs.
+    pub fn draw_text_on_path(
+        &mut self,
+        path: &Path,
+        text: impl ToString,
+        style: impl Into<TextStyle>,
+        layout: &PathTextLayout
+    ) -> Result<geometry::Rect, OverlayError> {
+        let text = text.to_string();
+        let style = style.into();
+
+        let Some(contour) = ContourMeasureIter::new(path, false, None).next() else {
+            return Ok(geometry::Rect::default());
+        };
+
+        let metrics = self.shaped_text(&style, &text).metrics;
+        let start = layout.start_distance(contour.length(), metrics.width);
+
+        // Distance from the path to the baseline, measured along the glyph's down direction
+        let (_, baseline_shift) = style.anchor.baseline_origin((0.0, 0.0), &metrics);
+
+        let mut blobs = Vec::new();
+        let mut ink_corners = Vec::new();
+
+        for run in glyph_runs(&self.fonts, &ShapeKey::new(&text, &style)) {
+            let mut widths = vec![0.0; run.glyphs.len()];
+            run.font.get_widths(&run.glyphs, &mut widths);
+
+            let mut glyph_bounds = vec![Rect::default(); run.glyphs.len()];
+            run.font.get_bounds(&run.glyphs, &mut glyph_bounds, None);
+
+            let mut glyphs = Vec::new();
+            let mut xforms = Vec::new();
+
+            for (index, glyph) in run.glyphs.iter().enumerate() {
+                let half_width = widths[index] / 2.0;
+                let position = run.positions[index];
+
+                // Glyphs are placed by their horizontal center so they hug curves evenly.
+                // Skia clamps distances to the path, so glyphs past either end are skipped here.
+                let distance = start + position.x + half_width;
+                if distance < 0.0 || distance > contour.length() {
+                    continue;
+                }
+                let Some((point, tangent)) = contour.pos_tan(distance) else {
+                    continue;
+                };
+                let placement = GlyphPlacement::new(point, tangent, layout.orientation, half_width, position.y + baseline_shift);
+
+                let bounds = glyph_bounds[index];
+                if !bounds.is_empty() {
+                    let corners = [
+                        Point::new(bounds.left, bounds.top),
+                        Point::new(bounds.right, bounds.top),
+                        Point::new(bounds.right, bounds.bottom),
+                        Point::new(bounds.left, bounds.bottom),
+                    ];
+                    ink_corners.extend(corners.map(|corner| placement.map(corner)));
+                }
+
+                glyphs.push(*glyph);
+                xforms.push(RSXform::new(placement.cos, placement.sin, placement.origin));
+            }
+
+            if let Some(text_blob) = TextBlob::from_rsxform(glyphs.as_slice(), &xforms, &run.font) {
+                blobs.push((text_blob, Point::default()));
+            }
+        }
+
+        let mut ink_bounds = Rect::new_empty();
+        if !ink_corners.is_empty() {
+            ink_bounds.set_bounds(&ink_corners);
+        }
+
+        let canvas = self.skia_context.as_mut()
+            .expect("Skia context should be initialized")
+            .canvas();
+
+        draw_text_blobs(canvas, &blobs, &style, ink_bounds);
+
+        Ok(ink_bounds.into())
+    }
+
+    /// Draws text along a circular arc around `center`. Angles are `(start, sweep)` in degrees,
+    /// clockwise from the positive x axis, a negative sweep runs counter-clockwise.
+    pub fn draw_text_on_arc(
+        &mut self,
+        center: impl Into<geometry::Point>,
+        radius: f32,
+        (start_angle, sweep_angle): (f32, f32),
+        text: impl ToString,
+        style: impl Into<TextStyle>,
+        layout: &PathTextLayout
+    ) -> Result<geometry::Rect, OverlayError> {
+        let center: geometry::Point = center.into();
+        let oval = Rect::new(center.x - radius, center.y - radius, center.x + radius, center.y + radius);
+
+        let mut path = Path::new();
+        path.add_arc(oval, start_angle, sweep_angle);
+
+        self.draw_text_on_path(&path, text, style, layout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_distance() {
+        let layout = PathTextLayout::new(PathTextAlign::Start);
+        assert_eq!(layout.start_distance(100.0, 40.0), 0.0);
+        assert_eq!(PathTextLayout::new(PathTextAlign::Center).start_distance(100.0, 40.0), 30.0);
+        assert_eq!(PathTextLayout::new(PathTextAlign::End).with_offset(-5.0).start_distance(100.0, 40.0), 55.0);
+    }
+
+    #[test]
+    fn test_glyph_placement() {
+        // Path heading straight down, glyphs turn a quarter clockwise
+        let point = Point::new(50.0, 50.0);
+        let down = Point::new(0.0, 1.0);
+
+        let rotated = GlyphPlacement::new(point, down, GlyphOrientation::Rotated, 4.0, 2.0);
+        assert_eq!((rotated.cos, rotated.sin), (0.0, 1.0));
+        assert_eq!(rotated.origin, Point::new(48.0, 46.0));
+        assert_eq!(rotated.map(Point::new(8.0, 0.0)), Point::new(48.0, 54.0));
+
+        // Upright glyphs only move to the point
+        let upright = GlyphPlacement::new(point, down, GlyphOrientation::Upright, 4.0, 2.0);
+        assert_eq!(upright.origin, Point::new(46.0, 52.0));
+        assert_eq!(upright.map(Point::new(8.0, 0.0)), Point::new(54.0, 52.0));
+    }
+}
@@ -0,0 +1,843 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use serde_json::{Map, Value};
+use skia_safe::Paint;
+use crate::core::{
+    Brush, Color, FontFeature, FontHandle, FontVariation, HorizontalAnchor, HueDirection, Insets,
+    InterpolationSpace, Overlay, OverlayError, TextAnchor, TextBackground, TextOutline, TextShadow, TextShaping,
+    TextStyle, VerticalAnchor,
+    geometry,
+    helper::to_color_4f
+};
+
+/// A problem found while loading a theme. `key` is the dotted path of the offending entry, e.g. `text.title.size`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThemeError {
+    pub key: String,
+    pub message: String,
+}
+
+impl ThemeError {
+    fn new(key: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { key: key.into(), message: message.into() }
+    }
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.key.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.key, self.message)
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {}
+
+/// A text style that refers to its font by name, so one theme works with any overlay that
+/// registered the same names with `Overlay::set_font_name`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ThemeTextStyle {
+    /// Font name, `None` uses the overlay's default font
+    pub font: Option<String>,
+    pub size: Option<f32>,
+    pub bold: bool,
+    pub italic: bool,
+    /// Everything except the font, `style.font` is ignored
+    pub style: TextStyle,
+}
+
+/// Look of a box drawn behind other content, like a panel or a label plate
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ComponentStyle {
+    pub background: Option<Brush>,
+    pub border: Option<Color>,
+    pub border_width: f32,
+    pub corner_radius: f32,
+    /// Space between the edge of the box and its content
+    pub padding: Insets,
+    /// Key of the text style used for text inside the component
+    pub text_style: Option<String>,
+}
+
+/// Named colors, brushes, text styles and component styles that draw calls refer to by key.
+/// Loading a theme file merges it over `Theme::default()`, so a file only lists what it changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub colors: HashMap<String, Color>,
+    pub brushes: HashMap<String, Brush>,
+    pub text_styles: HashMap<String, ThemeTextStyle>,
+    pub components: HashMap<String, ComponentStyle>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        let text = Color::WHITE;
+        let muted = Color::rgb(179, 179, 179);
+        let border = Color::rgba(255, 255, 255, 51);
+
+        let colors = HashMap::from([
+            ("text".to_string(), text),
+            ("text_muted".to_string(), muted),
+            ("text_dark".to_string(), Color::rgb(17, 17, 17)),
+            ("background".to_string(), Color::rgba(16, 16, 16, 204)),
+            ("border".to_string(), border),
+            ("accent".to_string(), Color::rgb(118, 185, 0)),
+            ("warning".to_string(), Color::rgb(255, 176, 0)),
+            ("error".to_string(), Color::rgb(255, 64, 64)),
+        ]);
+
+        let panel = Brush::linear_gradient(Color::rgba(26, 26, 26, 230), Color::rgba(13, 13, 13, 230), true)
+            .with_interpolation(InterpolationSpace::Oklab);
+        let brushes = HashMap::from([
+            ("panel".to_string(), panel.clone()),
+            ("accent".to_string(), Brush::Solid(colors["accent"])),
+        ]);
+
+        let text_style = |bold: bool, color: Color| ThemeTextStyle {
+            bold,
+            style: TextStyle::new(FontHandle::DEFAULT, color),
+            ..Default::default()
+        };
+        let text_styles = HashMap::from([
+            ("body".to_string(), text_style(false, text)),
+            ("title".to_string(), text_style(true, text)),
+            ("caption".to_string(), text_style(false, muted)),
+            ("label".to_string(), text_style(false, text)),
+        ]);
+
+        let components = HashMap::from([
+            ("panel".to_string(), ComponentStyle {
+                background: Some(panel),
+                border: Some(border),
+                border_width: 1.0,
+                corner_radius: 6.0,
+                padding: Insets::symmetric(8.0, 6.0),
+                text_style: Some("body".to_string()),
+            }),
+            ("label".to_string(), ComponentStyle {
+                background: Some(Brush::Solid(colors["background"])),
+                border: None,
+                border_width: 1.0,
+                corner_radius: 4.0,
+                padding: Insets::symmetric(6.0, 3.0),
+                text_style: Some("label".to_string()),
+            }),
+        ]);
+
+        Self { colors, brushes, text_styles, components }
+    }
+}
+
+impl Theme {
+    /// Parses a TOML theme and merges it over the default theme
+    pub fn from_toml_str(source: &str) -> Result<Self, ThemeError> {
+        let mut theme = Self::default();
+        theme.merge_toml_str(source)?;
+        Ok(theme)
+    }
+
+    /// Parses a JSON theme and merges it over the default theme
+    pub fn from_json_str(source: &str) -> Result<Self, ThemeError> {
+        let mut theme = Self::default();
+        theme.merge_json_str(source)?;
+        Ok(theme)
+    }
+
+    /// Loads a `.toml` or `.json` theme file and merges it over the default theme
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ThemeError> {
+        let mut theme = Self::default();
+        theme.merge_file(path)?;
+        Ok(theme)
+    }
+
+    /// Merges a TOML theme over this one. Nothing changes if the source has an error.
+    pub fn merge_toml_str(&mut self, source: &str) -> Result<(), ThemeError> {
+        let root: Value = toml::from_str(source)
+            .map_err(|err| ThemeError::new("", format!("invalid TOML: {}", err)))?;
+        self.merge_value(&root)
+    }
+
+    /// Merges a JSON theme over this one. Nothing changes if the source has an error.
+    pub fn merge_json_str(&mut self, source: &str) -> Result<(), ThemeError> {
+        let root: Value = serde_json::from_str(source)
+            .map_err(|err| ThemeError::new("", format!("invalid JSON: {}", err)))?;
+        self.merge_value(&root)
+    }
+
+    /// Merges a `.toml` or `.json` theme file over this one, picking the format by extension
+    pub fn merge_file(&mut self, path: impl AsRef<Path>) -> Result<(), ThemeError> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)
+            .map_err(|err| ThemeError::new("", format!("failed to read {}: {}", path.display(), err)))?;
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => self.merge_toml_str(&source),
+            Some("json") => self.merge_json_str(&source),
+            _ => Err(ThemeError::new("", format!("{} is not a .toml or .json file", path.display()))),
+        }
+    }
+
+    pub fn color(&self, key: &str) -> Option<Color> {
+        self.colors.get(key).copied()
+    }
+
+    pub fn brush(&self, key: &str) -> Option<&Brush> {
+        self.brushes.get(key)
+    }
+
+    pub fn text_style(&self, key: &str) -> Option<&ThemeTextStyle> {
+        self.text_styles.get(key)
+    }
+
+    pub fn component(&self, key: &str) -> Option<&ComponentStyle> {
+        self.components.get(key)
+    }
+
+    fn merge_value(&mut self, root: &Value) -> Result<(), ThemeError> {
+        // Applied to a copy so a bad file leaves the theme untouched
+        let mut merged = self.clone();
+        let root = table(root, "")?;
+
+        for section in root.keys() {
+            if !matches!(section.as_str(), "colors" | "brushes" | "text" | "components") {
+                return Err(ThemeError::new(section.as_str(), "unknown section, expected colors, brushes, text or components"));
+            }
+        }
+
+        // Later sections refer to entries of earlier ones
+        if let Some(colors) = root.get("colors") {
+            merged.merge_colors(table(colors, "colors")?)?;
+        }
+        if let Some(brushes) = root.get("brushes") {
+            merged.merge_brushes(table(brushes, "brushes")?)?;
+        }
+        if let Some(text_styles) = root.get("text") {
+            for (name, value) in table(text_styles, "text")? {
+                merged.merge_text_style(name, value)?;
+            }
+        }
+        if let Some(components) = root.get("components") {
+            for (name, value) in table(components, "components")? {
+                merged.merge_component(name, value)?;
+            }
+        }
+
+        *self = merged;
+        Ok(())
+    }
+
+    fn merge_colors(&mut self, colors: &Map<String, Value>) -> Result<(), ThemeError> {
+        for (name, value) in settle_order(colors, "colors")? {
+            let color = self.parse_color(value, &join_key("colors", name))?;
+            self.colors.insert(name.clone(), color);
+        }
+        Ok(())
+    }
+
+    fn merge_brushes(&mut self, brushes: &Map<String, Value>) -> Result<(), ThemeError> {
+        for (name, value) in settle_order(brushes, "brushes")? {
+            let brush = self.parse_brush(value, &join_key("brushes", name))?;
+            self.brushes.insert(name.clone(), brush);
+        }
+        Ok(())
+    }
+
+    fn merge_text_style(&mut self, name: &str, value: &Value) -> Result<(), ThemeError> {
+        let key = join_key("text", name);
+        let mut text_style = self.text_styles.get(name).cloned().unwrap_or_else(|| ThemeTextStyle {
+            style: TextStyle::new(FontHandle::DEFAULT, self.color("text").unwrap_or(Color::WHITE)),
+            ..Default::default()
+        });
+
+        for (field, value) in table(value, &key)? {
+            let key = join_key(&key, field);
+            let style = &mut text_style.style;
+
+            match field.as_str() {
+                "font" => text_style.font = match string(value, &key)? {
+                    "default" => None,
+                    name => Some(name.to_string()),
+                },
+                "size" => text_style.size = Some(positive(value, &key)?),
+                "bold" => text_style.bold = boolean(value, &key)?,
+                "italic" => text_style.italic = boolean(value, &key)?,
+                "color" => style.color = self.parse_color(value, &key)?,
+                "brush" => style.brush = Some(self.parse_brush(value, &key)?),
+                "outline" => style.outline = Some(self.parse_outline(value, &key)?),
+                "shadow" => style.shadow = Some(self.parse_shadow(value, &key)?),
+                "underline" => style.decoration.underline = boolean(value, &key)?,
+                "strikethrough" => style.decoration.strikethrough = boolean(value, &key)?,
+                "overline" => style.decoration.overline = boolean(value, &key)?,
+                "decoration_color" => style.decoration.color = Some(self.parse_color(value, &key)?),
+                "background" => style.background = Some(self.parse_background(value, &key)?),
+                "anchor" => style.anchor = parse_anchor(value, &key)?,
+                "shaping" => style.shaping = match string(value, &key)? {
+                    "simple" => TextShaping::Simple,
+                    "ltr" => TextShaping::LeftToRight,
+                    "rtl" => TextShaping::RightToLeft,
+                    other => return Err(ThemeError::new(key, format!("unknown shaping '{}', expected simple, ltr or rtl", other))),
+                },
+                "features" => style.features = parse_features(value, &key)?,
+                "variations" => style.variations = parse_variations(value, &key)?,
+                _ => return Err(unknown_key(key)),
+            }
+        }
+
+        self.text_styles.insert(name.to_string(), text_style);
+        Ok(())
+    }
+
+    fn merge_component(&mut self, name: &str, value: &Value) -> Result<(), ThemeError> {
+        let key = join_key("components", name);
+        let mut component = self.components.get(name).cloned().unwrap_or(ComponentStyle {
+            border_width: 1.0,
+            ..Default::default()
+        });
+
+        for (field, value) in table(value, &key)? {
+            let key = join_key(&key, field);
+
+            match field.as_str() {
+                "background" => component.background = Some(self.parse_brush(value, &key)?),
+                "border" => component.border = Some(self.parse_color(value, &key)?),
+                "border_width" => component.border_width = non_negative(value, &key)?,
+                "corner_radius" => component.corner_radius = non_negative(value, &key)?,
+                "padding" => component.padding = parse_insets(value, &key)?,
+                "text" => {
+                    let text_style = string(value, &key)?;
+                    if !self.text_styles.contains_key(text_style) {
+                        return Err(ThemeError::new(key, format!("unknown text style '{}'", text_style)));
+                    }
+                    component.text_style = Some(text_style.to_string());
+                }
+                _ => return Err(unknown_key(key)),
+            }
+        }
+
+        self.components.insert(name.to_string(), component);
+        Ok(())
+    }
+
+    /// A theme color name, CSS color name, `#` prefixed hex code, or `[r, g, b]` / `[r, g, b, a]` array
+    fn parse_color(&self, value: &Value, key: &str) -> Result<Color, ThemeError> {
+        match value {
+            Value::String(name) => self.color(name)
+                .or_else(|| name.parse().ok())
+                .ok_or_else(|| ThemeError::new(key, format!(
+                    "unknown color '{}', expected a theme color, a CSS color name or a hex code", name
+                ))),
+            Value::Array(channels) if matches!(channels.len(), 3 | 4) => {
+                let mut rgba = [255; 4];
+                for (index, channel) in channels.iter().enumerate() {
+                    rgba[index] = channel.as_u64()
+                        .and_then(|channel| u8::try_from(channel).ok())
+                        .ok_or_else(|| ThemeError::new(key, "color channels must be integers from 0 to 255"))?;
+                }
+                Ok(Color::rgba(rgba[0], rgba[1], rgba[2], rgba[3]))
+            }
+            _ => Err(ThemeError::new(key, "expected a color string or an [r, g, b, a] array")),
+        }
+    }
+
+    /// A theme brush name, any color, or a `{ linear = [...] }` / `{ radial = [...] }` gradient table
+    fn parse_brush(&self, value: &Value, key: &str) -> Result<Brush, ThemeError> {
+        if let Value::String(name) = value {
+            if let Some(brush) = self.brush(name) {
+                return Ok(brush.clone());
+            }
+        }
+        if !value.is_object() {
+            return self.parse_color(value, key).map(Brush::Solid);
+        }
+
+        let mut colors = None;
+        let mut radial = false;
+        let mut is_vertical = false;
+        let mut space = InterpolationSpace::default();
+
+        for (field, value) in table(value, key)? {
+            let key = join_key(key, field);
+
+            match field.as_str() {
+                "linear" | "radial" => {
+                    if colors.is_some() {
+                        return Err(ThemeError::new(key, "a gradient is either linear or radial"));
+                    }
+                    let stops = value.as_array()
+                        .filter(|stops| stops.len() >= 2)
+                        .ok_or_else(|| ThemeError::new(&key, "expected an array of at least two colors"))?;
+                    colors = Some(stops.iter()
+                        .map(|stop| self.parse_color(stop, &key))
+                        .collect::<Result<Vec<_>, _>>()?);
+                    radial = field == "radial";
+                }
+                "vertical" => is_vertical = boolean(value, &key)?,
+                "space" => space = parse_space(value, &key)?,
+                _ => return Err(unknown_key(key)),
+            }
+        }
+
+        let colors = colors.ok_or_else(|| ThemeError::new(key, "gradient needs a 'linear' or 'radial' color list"))?;
+        Ok(if radial {
+            Brush::RadialGradient { colors, space }
+        } else {
+            Brush::LinearGradient { colors, is_vertical, space }
+        })
+    }
+
+    fn parse_outline(&self, value: &Value, key: &str) -> Result<TextOutline, ThemeError> {
+        let mut outline = TextOutline { width: 1.0, color: Color::BLACK };
+
+        for (field, value) in table(value, key)? {
+            let key = join_key(key, field);
+            match field.as_str() {
+                "width" => outline.width = positive(value, &key)?,
+                "color" => outline.color = self.parse_color(value, &key)?,
+                _ => return Err(unknown_key(key)),
+            }
+        }
+
+        Ok(outline)
+    }
+
+    fn parse_shadow(&self, value: &Value, key: &str) -> Result<TextShadow, ThemeError> {
+        let mut shadow = TextShadow { offset: (1.0, 1.0), blur: 0.0, color: Color::rgba(0, 0, 0, 160) };
+
+        for (field, value) in table(value, key)? {
+            let key = join_key(key, field);
+            match field.as_str() {
+                "offset" => shadow.offset = pair(value, &key)?,
+                "blur" => shadow.blur = non_negative(value, &key)?,
+                "color" => shadow.color = self.parse_color(value, &key)?,
+                _ => return Err(unknown_key(key)),
+            }
+        }
+
+        Ok(shadow)
+    }
+
+    fn parse_background(&self, value: &Value, key: &str) -> Result<TextBackground, ThemeError> {
+        let mut background = TextBackground {
+            brush: Brush::Solid(self.color("background").unwrap_or(Color::BLACK)),
+            padding: (0.0, 0.0),
+            corner_radius: 0.0,
+        };
+
+        for (field, value) in table(value, key)? {
+            let key = join_key(key, field);
+            match field.as_str() {
+                "brush" => background.brush = self.parse_brush(value, &key)?,
+                "padding" => background.padding = match value {
+                    Value::Array(_) => pair(value, &key)?,
+                    _ => {
+                        let padding = non_negative(value, &key)?;
+                        (padding, padding)
+                    }
+                },
+                "radius" => background.corner_radius = non_negative(value, &key)?,
+                _ => return Err(unknown_key(key)),
+            }
+        }
+
+        Ok(background)
+    }
+}
+
+// VALUE PARSING --------------------------
+
+fn join_key(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", parent, name)
+    }
+}
+
+fn unknown_key(key: String) -> ThemeError {
+    ThemeError::new(key, "unknown key")
+}
+
+/// Entries of a colors or brushes table ordered so that one naming another entry of the same table comes
+/// after it. The result doesn't depend on key order (JSON objects come back sorted). Names the file doesn't
+/// define, and an entry naming itself, resolve against the theme so far.
+fn settle_order<'a>(entries: &'a Map<String, Value>, section: &str) -> Result<Vec<(&'a String, &'a Value)>, ThemeError> {
+    let mut settled: HashSet<&str> = HashSet::new();
+    let mut order = Vec::with_capacity(entries.len());
+    let mut pending: Vec<(&String, &Value)> = entries.iter().collect();
+
+    while !pending.is_empty() {
+        let mut waiting = Vec::new();
+
+        for (name, value) in pending.iter().copied() {
+            let depends_on_file = value.as_str()
+                .is_some_and(|reference| reference != name.as_str() && entries.contains_key(reference) && !settled.contains(reference));
+            if depends_on_file {
+                waiting.push((name, value));
+                continue;
+            }
+
+            order.push((name, value));
+            settled.insert(name.as_str());
+        }
+
+        if let Some((name, _)) = waiting.first().filter(|_| waiting.len() == pending.len()) {
+            return Err(ThemeError::new(join_key(section, name), format!("{} refer to each other in a cycle", section)));
+        }
+        pending = waiting;
+    }
+
+    Ok(order)
+}
+
+fn table<'a>(value: &'a Value, key: &str) -> Result<&'a Map<String, Value>, ThemeError> {
+    value.as_object().ok_or_else(|| ThemeError::new(key, "expected a table"))
+}
+
+fn string<'a>(value: &'a Value, key: &str) -> Result<&'a str, ThemeError> {
+    value.as_str().ok_or_else(|| ThemeError::new(key, "expected a string"))
+}
+
+fn boolean(value: &Value, key: &str) -> Result<bool, ThemeError> {
+    value.as_bool().ok_or_else(|| ThemeError::new(key, "expected true or false"))
+}
+
+fn number(value: &Value, key: &str) -> Result<f32, ThemeError> {
+    value.as_f64()
+        .map(|number| number as f32)
+        .ok_or_else(|| ThemeError::new(key, "expected a number"))
+}
+
+fn positive(value: &Value, key: &str) -> Result<f32, ThemeError> {
+    let number = number(value, key)?;
+    if number > 0.0 {
+        Ok(number)
+    } else {
+        Err(ThemeError::new(key, format!("expected a number above 0, got {}", number)))
+    }
+}
+
+fn non_negative(value: &Value, key: &str) -> Result<f32, ThemeError> {
+    let number = number(value, key)?;
+    if number >= 0.0 {
+        Ok(number)
+    } else {
+        Err(ThemeError::new(key, format!("expected a number of at least 0, got {}", number)))
+    }
+}
+
+fn numbers(value: &Value, key: &str) -> Result<Vec<f32>, ThemeError> {
+    value.as_array()
+        .ok_or_else(|| ThemeError::new(key, "expected an array of numbers"))?
+        .iter()
+        .map(|number_value| number(number_value, key))
+        .collect()
+}
+
+fn pair(value: &Value, key: &str) -> Result<(f32, f32), ThemeError> {
+    match numbers(value, key)?.as_slice() {
+        [x, y] => Ok((*x, *y)),
+        _ => Err(ThemeError::new(key, "expected two numbers")),
+    }
+}
+
+/// One number for every side, `[horizontal, vertical]` or `[top, right, bottom, left]`
+fn parse_insets(value: &Value, key: &str) -> Result<Insets, ThemeError> {
+    if !value.is_array() {
+        return non_negative(value, key).map(Insets::uniform);
+    }
+
+    match numbers(value, key)?.as_slice() {
+        [horizontal, vertical] => Ok(Insets::symmetric(*horizontal, *vertical)),
+        [top, right, bottom, left] => Ok(Insets::new(*top, *right, *bottom, *left)),
+        _ => Err(ThemeError::new(key, "expected a number, [horizontal, vertical] or [top, right, bottom, left]")),
+    }
+}
+
+fn parse_space(value: &Value, key: &str) -> Result<InterpolationSpace, ThemeError> {
+    Ok(match string(value, key)? {
+        "srgb" => InterpolationSpace::Srgb,
+        "linear-srgb" => InterpolationSpace::LinearSrgb,
+        "oklab" => InterpolationSpace::Oklab,
+        "oklch" => InterpolationSpace::Oklch(HueDirection::Shorter),
+        "oklch-longer" => InterpolationSpace::Oklch(HueDirection::Longer),
+        "oklch-increasing" => InterpolationSpace::Oklch(HueDirection::Increasing),
+        "oklch-decreasing" => InterpolationSpace::Oklch(HueDirection::Decreasing),
+        other => return Err(ThemeError::new(key, format!(
+            "unknown interpolation space '{}', expected srgb, linear-srgb, oklab or oklch[-longer|-increasing|-decreasing]",
+            other
+        ))),
+    })
+}
+
+fn parse_anchor(value: &Value, key: &str) -> Result<TextAnchor, ThemeError> {
+    let mut anchor = TextAnchor::default();
+
+    for (field, value) in table(value, key)? {
+        let key = join_key(key, field);
+        match field.as_str() {
+            "horizontal" => anchor.horizontal = match string(value, &key)? {
+                "left" => HorizontalAnchor::Left,
+                "center" => HorizontalAnchor::Center,
+                "right" => HorizontalAnchor::Right,
+                other => return Err(ThemeError::new(key, format!("unknown anchor '{}', expected left, center or right", other))),
+            },
+            "vertical" => anchor.vertical = match string(value, &key)? {
+                "top" => VerticalAnchor::Top,
+                "middle" => VerticalAnchor::Middle,
+                "baseline" => VerticalAnchor::Baseline,
+                "bottom" => VerticalAnchor::Bottom,
+                other => return Err(ThemeError::new(key, format!(
+                    "unknown anchor '{}', expected top, middle, baseline or bottom", other
+                ))),
+            },
+            _ => return Err(unknown_key(key)),
+        }
+    }
+
+    Ok(anchor)
+}
+
+fn four_byte_tag(name: &str, key: &str) -> Result<[u8; 4], ThemeError> {
+    name.as_bytes()
+        .try_into()
+        .ok()
+        .filter(|tag: &[u8; 4]| tag.is_ascii())
+        .ok_or_else(|| ThemeError::new(key, "OpenType tags are exactly four ASCII characters"))
+}
+
+/// `{ tnum = 1, liga = false }`
+fn parse_features(value: &Value, key: &str) -> Result<Vec<FontFeature>, ThemeError> {
+    table(value, key)?.iter()
+        .map(|(tag, value)| -> Result<FontFeature, ThemeError> {
+            let key = join_key(key, tag);
+            let value = match value {
+                Value::Bool(enabled) => u32::from(*enabled),
+                _ => value.as_u64()
+                    .and_then(|value| u32::try_from(value).ok())
+                    .ok_or_else(|| ThemeError::new(&key, "expected true, false or a feature value"))?,
+            };
+            Ok(FontFeature::new(four_byte_tag(tag, &key)?, value))
+        })
+        .collect()
+}
+
+/// `{ wght = 650, wdth = 90 }`
+fn parse_variations(value: &Value, key: &str) -> Result<Vec<FontVariation>, ThemeError> {
+    table(value, key)?.iter()
+        .map(|(axis, value)| -> Result<FontVariation, ThemeError> {
+            let key = join_key(key, axis);
+            Ok(FontVariation::new(four_byte_tag(axis, &key)?, number(value, &key)?))
+        })
+        .collect()
+}
+
+impl Overlay {
+    // THEME FUNCTIONS ------------------------
+
+    /// Replaces the theme. Fails if a text style names a font that wasn't registered with `set_font_name`.
+    pub fn set_theme(&mut self, theme: Theme) -> Result<(), OverlayError> {
+        for text_style in theme.text_styles.values() {
+            if let Some(name) = text_style.font.as_deref().filter(|name| self.fonts.by_name(name).is_none()) {
+                return Err(OverlayError::FontNotFound(name.to_string()));
+            }
+        }
+
+        self.theme = theme;
+        Ok(())
+    }
+
+    /// Loads a `.toml` or `.json` theme file over the default theme and makes it current
+    pub fn load_theme(&mut self, path: impl AsRef<Path>) -> Result<(), OverlayError> {
+        let theme = Theme::load(path).map_err(OverlayError::InvalidTheme)?;
+        self.set_theme(theme)
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    pub fn theme_color(&self, key: &str) -> Result<Color, OverlayError> {
+        self.theme.color(key).ok_or_else(|| OverlayError::ThemeKeyNotFound(join_key("colors", key)))
+    }
+
+    pub fn theme_brush(&self, key: &str) -> Result<Brush, OverlayError> {
+        self.theme.brush(key).cloned().ok_or_else(|| OverlayError::ThemeKeyNotFound(join_key("brushes", key)))
+    }
+
+    /// Resolves a theme text style into a `TextStyle` for this overlay's fonts
+    pub fn theme_text_style(&mut self, key: &str) -> Result<TextStyle, OverlayError> {
+        let text_style = self.theme.text_style(key)
+            .ok_or_else(|| OverlayError::ThemeKeyNotFound(join_key("text", key)))?;
+
+        let base = match text_style.font.as_deref() {
+            Some(name) => self.fonts.by_name(name).ok_or_else(|| OverlayError::FontNotFound(name.to_string()))?,
+            None => FontHandle::DEFAULT,
+        };
+        let font = if text_style.bold || text_style.italic || text_style.size.is_some() {
+            self.fonts.variant(base, text_style.bold, text_style.italic, text_style.size)
+        } else {
+            base
+        };
+
+        Ok(TextStyle { font, ..text_style.style.clone() })
+    }
+
+    pub fn theme_component(&self, key: &str) -> Result<ComponentStyle, OverlayError> {
+        self.theme.component(key).cloned().ok_or_else(|| OverlayError::ThemeKeyNotFound(join_key("components", key)))
+    }
+
+    /// Draws text with a theme text style
+    pub fn draw_themed_text(
+        &mut self,
+        pos: impl Into<geometry::Point>,
+        text: impl ToString,
+        key: &str
+    ) -> Result<geometry::Rect, OverlayError> {
+        let style = self.theme_text_style(key)?;
+        self.draw_text_with(pos, text, style)
+    }
+
+    /// Draws a component's box. Returns the area inside its padding for the content.
+    pub fn draw_component(
+        &mut self,
+        pos: impl Into<geometry::Point>,
+        size: impl Into<geometry::Size>,
+        key: &str
+    ) -> Result<geometry::Rect, OverlayError> {
+        let component = self.theme_component(key)?;
+        let rect = geometry::Rect::from_origin_size(pos, size);
+
+        self.draw_component_box(rect, &component);
+
+        Ok(rect.inset(component.padding))
+    }
+
+    /// Draws a line of text on a box sized to fit it, with `pos` at the box's top left corner.
+    /// Returns the box.
+    pub fn draw_label(
+        &mut self,
+        pos: impl Into<geometry::Point>,
+        text: impl ToString,
+        key: &str
+    ) -> Result<geometry::Rect, OverlayError> {
+        let text = text.to_string();
+        let component = self.theme_component(key)?;
+        let style = match component.text_style.as_deref() {
+            Some(text_style) => self.theme_text_style(text_style)?,
+            None => TextStyle::default(),
+        }.with_anchor(TextAnchor::TOP_LEFT);
+        let style = match &component.background {
+            Some(background) => self.readable_on(style, background.average_color()),
+            None => style,
+        };
+
+        let metrics = self.measure_text_with(&text, &style);
+        let content = geometry::Size::new(metrics.width, metrics.height());
+        let padding = component.padding;
+        let rect = geometry::Rect::from_origin_size(
+            pos,
+            content + geometry::Size::new(padding.horizontal(), padding.vertical())
+        );
+
+        self.draw_component_box(rect, &component);
+        self.draw_text_with((rect.x + padding.left, rect.y + padding.top), text, style)?;
+
+        Ok(rect)
+    }
+
+    fn draw_component_box(&mut self, rect: geometry::Rect, component: &ComponentStyle) {
+        let canvas = self.skia_context.as_mut()
+            .expect("Skia context should be initialized")
+            .canvas();
+
+        let radius = component.corner_radius;
+        if let Some(background) = &component.background {
+            canvas.draw_round_rect(skia_safe::Rect::from(rect), radius, radius, &background.to_paint(rect.into()));
+        }
+
+        if let Some(border) = component.border.filter(|_| component.border_width > 0.0) {
+            let mut paint = Paint::new(to_color_4f(border), None);
+            paint.set_anti_alias(true);
+            paint.set_style(skia_safe::paint::Style::Stroke);
+            paint.set_stroke_width(component.border_width);
+
+            // Keep the stroke inside the box
+            let half = component.border_width / 2.0;
+            let stroke_radius = (radius - half).max(0.0);
+            canvas.draw_round_rect(skia_safe::Rect::from(rect.inset(half)), stroke_radius, stroke_radius, &paint);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_toml() {
+        let theme = Theme::from_toml_str(r##"
+            [colors]
+            highlight = "accent_dark"
+            accent_dark = "#336600"
+
+            [brushes]
+            header = { linear = ["highlight", "transparent"], space = "oklab" }
+
+            [text.title]
+            size = 24
+            color = "highlight"
+
+            [components.panel]
+            padding = [4, 2]
+        "##).expect("theme should parse");
+
+        assert_eq!(theme.color("highlight"), Some(Color::rgb(0x33, 0x66, 0x00)));
+        assert_eq!(theme.color("text"), Some(Color::WHITE));
+        assert!(matches!(theme.brush("header"), Some(Brush::LinearGradient { .. })));
+
+        // Fields the file didn't mention keep their defaults
+        let title = theme.text_style("title").unwrap();
+        assert_eq!(title.size, Some(24.0));
+        assert!(title.bold);
+        assert_eq!(theme.component("panel").unwrap().padding, Insets::symmetric(4.0, 2.0));
+        assert_eq!(theme.component("panel").unwrap().corner_radius, 6.0);
+    }
+
+    #[test]
+    fn test_color_references_prefer_the_file() {
+        // JSON keys come back sorted, "a_highlight" is read before the "accent" it refers to
+        let theme = Theme::from_json_str(r##"{"colors": {"a_highlight": "accent", "accent": "#0000ff", "b": "error"}}"##)
+            .expect("theme should parse");
+
+        assert_eq!(theme.color("a_highlight"), Some(Color::BLUE));
+        assert_eq!(theme.color("b"), Theme::default().color("error"));
+    }
+
+    #[test]
+    fn test_brush_references_prefer_the_file() {
+        // "accent" sorts before the "panel_bg" gradient it names
+        let theme = Theme::from_json_str(r##"{"brushes": {"accent": "panel_bg", "panel_bg": {"linear": ["#000000", "#ffffff"]}}}"##)
+            .expect("theme should parse");
+
+        assert!(matches!(theme.brush("accent"), Some(Brush::LinearGradient { .. })));
+        assert_eq!(theme.brush("accent"), theme.brush("panel_bg"));
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |source: &str| Theme::from_json_str(source).unwrap_err().to_string();
+
+        assert_eq!(error(r#"{"text": {"title": {"colour": "red"}}}"#), "text.title.colour: unknown key");
+        assert_eq!(error(r#"{"text": {"title": {"size": -2}}}"#), "text.title.size: expected a number above 0, got -2");
+        assert_eq!(
+            error(r#"{"colors": {"a": "b"}}"#),
+            "colors.a: unknown color 'b', expected a theme color, a CSS color name or a hex code"
+        );
+        assert_eq!(error(r#"{"colors": {"a": "b", "b": "a"}}"#), "colors.a: colors refer to each other in a cycle");
+        assert_eq!(error(r#"{"brushes": {"a": "b", "b": "a"}}"#), "brushes.a: brushes refer to each other in a cycle");
+        assert!(error(r#"{"fonts": {}}"#).starts_with("fonts: unknown section"));
+    }
+}
@@ -10,7 +10,8 @@ mod tests {
 
     #[test]
     fn test_overlay() {
-        let mut overlay = Overlay::new("Tahoma", 18.0);
+        let mut overlay = Overlay::new("Tahoma", 18.0)
+            .expect("Failed to create overlay");
 
         // Initialize overlay
         match overlay.init() {